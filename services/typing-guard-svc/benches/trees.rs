@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use typing_guard_svc::{
+    models::features::TypingFeatures,
+    scoring::{Scorer, TreeEnsembleScorer},
+};
+
+fn tree_ensemble_inference(c: &mut Criterion) {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tree-ensemble-golden.json");
+    let scorer = TreeEnsembleScorer::from_file(path).unwrap();
+    let features = TypingFeatures {
+        events: 40,
+        iki_mean: 150.0,
+        iki_std: 50.0,
+        iki_iqr: 60.0,
+        burstiness: -0.5,
+        entropy: 2.8,
+        backspace_per_100: 5.0,
+        paste_events: 0,
+        jitter_mad: 25.0,
+        outlier_ratio: 0.03,
+    };
+
+    c.bench_function("tree_ensemble_score", |b| b.iter(|| scorer.score(black_box(&features))));
+}
+
+criterion_group!(benches, tree_ensemble_inference);
+criterion_main!(benches);
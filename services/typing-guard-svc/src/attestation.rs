@@ -0,0 +1,182 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{config::AttestationConfig, models::response::ScoreResponse, scoring::Scorer};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// JOSE header of every token, a JWT signed with HMAC-SHA256
+const HEADER: &str = r#"{"alg":"HS256","typ":"JWT"}"#;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AttestationError {
+    #[error("Token is not a well-formed JWT")]
+    Malformed,
+
+    #[error("Token is not signed with HS256")]
+    UnsupportedAlgorithm,
+
+    #[error("Token signature verification failed")]
+    InvalidSignature,
+
+    #[error("Token expired {0} s ago")]
+    Expired(i64),
+}
+
+/// What a token attests about one scored window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttestationClaims {
+    pub session_id: Uuid,
+
+    /// Score of the window, after any replay penalty
+    pub score: f64,
+
+    /// Label of the band containing the score
+    pub label: String,
+
+    /// Engine that scored the window
+    pub scorer: String,
+
+    /// Version of that engine's model, or of the service for built-in engines
+    pub scorer_version: String,
+
+    /// Issue time, in Unix seconds
+    pub iat: i64,
+
+    /// Expiry, in Unix seconds
+    pub exp: i64,
+}
+
+/// Signs the verdicts of `/score` into tokens a backend can check offline
+pub struct Attestor {
+    key: Vec<u8>,
+    ttl_secs: i64,
+}
+
+impl Attestor {
+    pub fn new(config: &AttestationConfig) -> Self {
+        Self {
+            key: config.key.as_bytes().to_vec(),
+            ttl_secs: config.ttl_secs as i64,
+        }
+    }
+
+    /// Token of a response's verdict for a session
+    pub fn attest(&self, session_id: Uuid, response: &ScoreResponse, scorer: &dyn Scorer) -> String {
+        let iat = chrono::Utc::now().timestamp();
+        let claims = AttestationClaims {
+            session_id,
+            score: response.score,
+            label: response.label.clone(),
+            scorer: scorer.name().to_string(),
+            scorer_version: scorer.version().unwrap_or(env!("CARGO_PKG_VERSION")).to_string(),
+            iat,
+            exp: iat + self.ttl_secs,
+        };
+        sign(&claims, &self.key)
+    }
+}
+
+/// Compact JWT of the claims, signed with HS256
+pub fn sign(claims: &AttestationClaims, key: &[u8]) -> String {
+    let claims = serde_json::to_vec(claims).expect("claims serialize to JSON");
+    let body = format!("{}.{}", URL_SAFE_NO_PAD.encode(HEADER), URL_SAFE_NO_PAD.encode(claims));
+    let signature = mac(&body, key).finalize().into_bytes();
+    format!("{}.{}", body, URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// Claims of a token, if its signature checks out with `key` and it hasn't expired
+pub fn verify(token: &str, key: &[u8]) -> Result<AttestationClaims, AttestationError> {
+    verify_at(chrono::Utc::now().timestamp(), token, key)
+}
+
+fn verify_at(now: i64, token: &str, key: &[u8]) -> Result<AttestationClaims, AttestationError> {
+    let (body, signature) = token.rsplit_once('.').ok_or(AttestationError::Malformed)?;
+    let (header, claims) = body.split_once('.').ok_or(AttestationError::Malformed)?;
+
+    // The algorithm is fixed, never taken from the token (no "none", no key confusion)
+    let header: serde_json::Value = decode(header)?;
+    if header["alg"] != "HS256" {
+        return Err(AttestationError::UnsupportedAlgorithm);
+    }
+
+    let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| AttestationError::Malformed)?;
+    mac(body, key)
+        .verify_slice(&signature)
+        .map_err(|_| AttestationError::InvalidSignature)?;
+
+    let claims: AttestationClaims = decode(claims)?;
+    if now >= claims.exp {
+        return Err(AttestationError::Expired(now - claims.exp));
+    }
+    Ok(claims)
+}
+
+/// JSON of a base64url segment
+fn decode<T: serde::de::DeserializeOwned>(segment: &str) -> Result<T, AttestationError> {
+    let bytes = URL_SAFE_NO_PAD.decode(segment).map_err(|_| AttestationError::Malformed)?;
+    serde_json::from_slice(&bytes).map_err(|_| AttestationError::Malformed)
+}
+
+fn mac(body: &str, key: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(body.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"attestation-test-key-of-32-bytes";
+    const NOW: i64 = 1_736_345_678;
+
+    fn claims() -> AttestationClaims {
+        AttestationClaims {
+            session_id: Uuid::new_v4(),
+            score: 0.78,
+            label: "likely_human".to_string(),
+            scorer: "logistic".to_string(),
+            scorer_version: "2025-01-07".to_string(),
+            iat: NOW,
+            exp: NOW + 300,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let claims = claims();
+        let token = sign(&claims, KEY);
+
+        // Any JWT library reads the header
+        assert!(token.starts_with("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9."));
+        assert_eq!(verify_at(NOW + 299, &token, KEY), Ok(claims));
+        assert_eq!(verify_at(NOW + 301, &token, KEY), Err(AttestationError::Expired(1)));
+    }
+
+    #[test]
+    fn test_tampered_tokens_rejected() {
+        let token = sign(&claims(), KEY);
+        assert_eq!(verify_at(NOW, &token, b"another-key"), Err(AttestationError::InvalidSignature));
+        assert_eq!(verify_at(NOW, "not-a-token", KEY), Err(AttestationError::Malformed));
+
+        // Raising the score breaks the signature
+        let (header, rest) = token.split_once('.').unwrap();
+        let signature = rest.split_once('.').unwrap().1;
+        let forged = AttestationClaims {
+            score: 0.99,
+            ..claims()
+        };
+        let forged_claims = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+        let forged_token = format!("{}.{}.{}", header, forged_claims, signature);
+        assert_eq!(verify_at(NOW, &forged_token, KEY), Err(AttestationError::InvalidSignature));
+
+        // An unsigned token doesn't pass for one
+        let unsigned = format!("{}.{}.", URL_SAFE_NO_PAD.encode(r#"{"alg":"none"}"#), forged_claims);
+        assert_eq!(verify_at(NOW, &unsigned, KEY), Err(AttestationError::UnsupportedAlgorithm));
+    }
+}
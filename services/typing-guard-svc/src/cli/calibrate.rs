@@ -0,0 +1,58 @@
+use std::error::Error;
+
+use crate::{
+    cli::{self, Args},
+    models::dataset,
+    scoring::Calibration,
+};
+
+/// Fit a calibration from raw scores of a labeled dataset
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let records = dataset::load(args.required("data")?)?;
+    if records.is_empty() {
+        return Err("Dataset is empty".into());
+    }
+
+    let (_, pipeline) = cli::load_pipeline()?;
+    let samples: Vec<(f64, bool)> = records
+        .iter()
+        .map(|record| (pipeline.raw_score(&record.payload), record.is_human()))
+        .collect();
+
+    let calibration = match args.get("method").unwrap_or("platt") {
+        "platt" => Calibration::fit_platt(&samples),
+        "isotonic" => Calibration::fit_isotonic(&samples),
+        other => return Err(format!("Unknown calibration method: {}", other).into()),
+    };
+
+    let calibrated: Vec<(f64, bool)> = samples
+        .iter()
+        .map(|&(score, human)| (calibration.apply(score), human))
+        .collect();
+    eprintln!("Records:      {}", samples.len());
+    eprintln!("Brier score:  raw {:.4}, calibrated {:.4}", brier(&samples), brier(&calibrated));
+    eprintln!("Log-loss:     raw {:.4}, calibrated {:.4}", log_loss(&samples), log_loss(&calibrated));
+
+    cli::write_output(args, &serde_json::to_string_pretty(&calibration)?)
+}
+
+/// Mean squared error of probabilities against labels
+fn brier(samples: &[(f64, bool)]) -> f64 {
+    samples
+        .iter()
+        .map(|&(p, human)| (p - if human { 1.0 } else { 0.0 }).powi(2))
+        .sum::<f64>()
+        / samples.len() as f64
+}
+
+/// Mean negative log-likelihood of labels, with probabilities clipped away from 0 and 1
+pub fn log_loss(samples: &[(f64, bool)]) -> f64 {
+    samples
+        .iter()
+        .map(|&(p, human)| {
+            let p = p.clamp(1e-6, 1.0 - 1e-6);
+            -if human { p.ln() } else { (1.0 - p).ln() }
+        })
+        .sum::<f64>()
+        / samples.len() as f64
+}
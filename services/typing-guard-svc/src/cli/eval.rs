@@ -0,0 +1,186 @@
+use std::{error::Error, fmt::Write};
+
+use crate::{
+    cli::{self, Args},
+    models::{dataset, response::Action},
+};
+
+/// Default ceiling on the share of humans flagged when searching for a threshold
+const DEFAULT_TARGET_FPR: f64 = 0.01;
+
+/// Outcomes of a detector against ground truth, with bots as the positive class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Confusion {
+    /// Bots flagged
+    pub true_positives: usize,
+    /// Humans flagged
+    pub false_positives: usize,
+    /// Humans allowed
+    pub true_negatives: usize,
+    /// Bots allowed
+    pub false_negatives: usize,
+}
+
+impl Confusion {
+    /// Tally `(flagged, human)` outcomes
+    pub fn count(outcomes: impl IntoIterator<Item = (bool, bool)>) -> Self {
+        let mut confusion = Self::default();
+        for (flagged, human) in outcomes {
+            match (flagged, human) {
+                (true, false) => confusion.true_positives += 1,
+                (true, true) => confusion.false_positives += 1,
+                (false, true) => confusion.true_negatives += 1,
+                (false, false) => confusion.false_negatives += 1,
+            }
+        }
+        confusion
+    }
+
+    /// Share of flagged sessions that are bots
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    /// Share of bots that are flagged
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    /// Share of humans that are flagged
+    pub fn false_positive_rate(&self) -> f64 {
+        ratio(self.false_positives, self.false_positives + self.true_negatives)
+    }
+}
+
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Probability that a random human outscores a random bot, ties counting half
+pub fn roc_auc(samples: &[(f64, bool)]) -> f64 {
+    let mut sorted: Vec<(f64, bool)> = samples.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Mann–Whitney U from the rank sum of humans, tied scores sharing their mean rank
+    let mut human_rank_sum = 0.0;
+    let mut start = 0;
+    while start < sorted.len() {
+        let end = start + sorted[start..].iter().take_while(|s| s.0 == sorted[start].0).count();
+        let mean_rank = (start + end + 1) as f64 / 2.0;
+        human_rank_sum += mean_rank * sorted[start..end].iter().filter(|s| s.1).count() as f64;
+        start = end;
+    }
+
+    let humans = samples.iter().filter(|s| s.1).count() as f64;
+    let bots = samples.len() as f64 - humans;
+    (human_rank_sum - humans * (humans + 1.0) / 2.0) / (humans * bots)
+}
+
+/// Highest threshold flagging at most `target_fpr` of humans, with its outcomes
+///
+/// Sessions scoring strictly below the threshold are flagged.
+pub fn threshold_at_fpr(samples: &[(f64, bool)], target_fpr: f64) -> (f64, Confusion) {
+    let mut human_scores: Vec<f64> = samples.iter().filter(|s| s.1).map(|s| s.0).collect();
+    human_scores.sort_by(f64::total_cmp);
+
+    let allowed = (target_fpr * human_scores.len() as f64).floor() as usize;
+    let threshold = human_scores.get(allowed).copied().unwrap_or(1.0);
+    let confusion = Confusion::count(samples.iter().map(|&(score, human)| (score < threshold, human)));
+    (threshold, confusion)
+}
+
+/// `--target-fpr` option, defaulting to 1%
+pub fn target_fpr(args: &Args) -> Result<f64, String> {
+    match args.get("target-fpr") {
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|fpr| (0.0..1.0).contains(fpr))
+            .ok_or_else(|| format!("--target-fpr must be a number in [0, 1), got {}", value)),
+        None => Ok(DEFAULT_TARGET_FPR),
+    }
+}
+
+/// Score a labeled dataset with the configured pipeline and report detection metrics
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let records = dataset::load(args.required("data")?)?;
+    let target_fpr = target_fpr(args)?;
+
+    let humans = records.iter().filter(|record| record.is_human()).count();
+    let bots = records.len() - humans;
+    if humans == 0 || bots == 0 {
+        return Err("Dataset needs both human and bot records".into());
+    }
+
+    let (_, pipeline) = cli::load_pipeline()?;
+    let mut samples = Vec::with_capacity(records.len());
+    let mut decisions = Vec::with_capacity(records.len());
+    for record in &records {
+        let response = pipeline.evaluate(&record.payload);
+        samples.push((response.score, record.is_human()));
+        decisions.push((response.action != Action::Allow, record.is_human()));
+    }
+
+    let configured = Confusion::count(decisions);
+    let (threshold, best) = threshold_at_fpr(&samples, target_fpr);
+
+    let mut report = String::new();
+    writeln!(report, "Records:    {} ({} human, {} bot)", records.len(), humans, bots)?;
+    writeln!(report, "ROC AUC:    {:.4}", roc_auc(&samples))?;
+    writeln!(report)?;
+    writeln!(report, "Configured decisions (bot = positive, flagged = challenge or block):")?;
+    writeln!(report, "  Precision {:.4}", configured.precision())?;
+    writeln!(report, "  Recall    {:.4}", configured.recall())?;
+    writeln!(report, "  FPR       {:.4}", configured.false_positive_rate())?;
+    writeln!(report)?;
+    writeln!(report, "               flagged   allowed")?;
+    writeln!(report, "  bot       {:>10} {:>9}", configured.true_positives, configured.false_negatives)?;
+    writeln!(report, "  human     {:>10} {:>9}", configured.false_positives, configured.true_negatives)?;
+    writeln!(report)?;
+    writeln!(report, "Best threshold for FPR <= {:.2}%:", target_fpr * 100.0)?;
+    writeln!(report, "  Threshold {:.4}", threshold)?;
+    writeln!(report, "  Precision {:.4}", best.precision())?;
+    writeln!(report, "  Recall    {:.4}", best.recall())?;
+    write!(report, "  FPR       {:.4}", best.false_positive_rate())?;
+
+    cli::write_output(args, &report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roc_auc() {
+        let separated = [(0.1, false), (0.2, false), (0.8, true), (0.9, true)];
+        assert_eq!(roc_auc(&separated), 1.0);
+
+        let reversed = [(0.9, false), (0.8, false), (0.2, true), (0.1, true)];
+        assert_eq!(roc_auc(&reversed), 0.0);
+
+        // One of four human/bot pairs inverted, one tied
+        let mixed = [(0.1, false), (0.5, false), (0.5, true), (0.3, true)];
+        assert_eq!(roc_auc(&mixed), (2.0 + 0.5) / 4.0);
+    }
+
+    #[test]
+    fn test_threshold_at_fpr() {
+        let mut samples: Vec<(f64, bool)> = (0..10).map(|i| (0.5 + i as f64 * 0.05, true)).collect();
+        samples.extend([(0.1, false), (0.52, false), (0.62, false), (0.9, false)]);
+
+        // One human in ten may be flagged: everything below the second-lowest human
+        let (threshold, confusion) = threshold_at_fpr(&samples, 0.1);
+        assert!((threshold - 0.55).abs() < 1e-12);
+        assert_eq!(confusion.false_positives, 1);
+        assert_eq!(confusion.true_positives, 2);
+        assert_eq!(confusion.recall(), 0.5);
+
+        let (threshold, confusion) = threshold_at_fpr(&samples, 0.0);
+        assert_eq!(threshold, 0.5);
+        assert_eq!(confusion.false_positive_rate(), 0.0);
+    }
+}
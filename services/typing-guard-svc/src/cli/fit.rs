@@ -0,0 +1,261 @@
+use std::{error::Error, fmt::Write};
+
+use crate::{
+    cli::{self, calibrate::log_loss, eval, Args},
+    models::{dataset, features::FeatureWeights},
+};
+
+/// Number of weighted heuristic features
+const FEATURES: usize = 8;
+
+/// Gradient steps of the log-loss fit
+const MAX_ITERATIONS: usize = 500;
+
+/// Weight moved between two features per step of the recall search, coarse to fine
+const TRANSFER_STEPS: [f64; 5] = [0.1, 0.05, 0.02, 0.01, 0.005];
+
+/// Sub-scores of one record with its label
+type Row = ([f64; FEATURES], bool);
+
+/// What the weights are fitted for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    /// Mean log-loss of the score as P(human)
+    LogLoss,
+
+    /// Share of bots flagged at the threshold flagging `target_fpr` of humans
+    RecallAtFpr,
+}
+
+/// Heuristic score of every row under the given weights
+fn scores(rows: &[Row], weights: &[f64; FEATURES]) -> Vec<(f64, bool)> {
+    rows.iter()
+        .map(|(values, human)| (dot(values, weights).clamp(0.0, 1.0), *human))
+        .collect()
+}
+
+fn dot(a: &[f64; FEATURES], b: &[f64; FEATURES]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Euclidean projection onto the probability simplex (Duchi et al., 2008)
+fn project_to_simplex(v: &[f64; FEATURES]) -> [f64; FEATURES] {
+    let mut sorted = *v;
+    sorted.sort_by(|a, b| b.total_cmp(a));
+
+    let mut cumulative = 0.0;
+    let mut theta = 0.0;
+    for (i, value) in sorted.iter().enumerate() {
+        cumulative += value;
+        let candidate = (cumulative - 1.0) / (i + 1) as f64;
+        if value - candidate > 0.0 {
+            theta = candidate;
+        }
+    }
+
+    v.map(|value| (value - theta).max(0.0))
+}
+
+/// Weights minimizing log-loss, by projected gradient descent with backtracking
+pub fn fit_log_loss(rows: &[Row], start: [f64; FEATURES]) -> [f64; FEATURES] {
+    let mut weights = project_to_simplex(&start);
+    let mut loss = log_loss(&scores(rows, &weights));
+    let mut step = 1.0;
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut gradient = [0.0; FEATURES];
+        for (values, human) in rows {
+            let p = dot(values, &weights).clamp(1e-6, 1.0 - 1e-6);
+            let slope = if *human { -1.0 / p } else { 1.0 / (1.0 - p) };
+            for (g, x) in gradient.iter_mut().zip(values) {
+                *g += slope * x / rows.len() as f64;
+            }
+        }
+
+        // Halve the step until the loss drops, grow it back after a success
+        let mut improved = false;
+        while step > 1e-9 {
+            let moved: [f64; FEATURES] = std::array::from_fn(|i| weights[i] - step * gradient[i]);
+            let candidate = project_to_simplex(&moved);
+            let candidate_loss = log_loss(&scores(rows, &candidate));
+            if candidate_loss < loss {
+                improved = loss - candidate_loss > 1e-10;
+                weights = candidate;
+                loss = candidate_loss;
+                step *= 2.0;
+                break;
+            }
+            step /= 2.0;
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    weights
+}
+
+/// Weights maximizing recall at the target FPR, by moving weight between pairs of features
+///
+/// Recall is piecewise constant in the weights, so ties go to the lower log-loss.
+pub fn fit_recall_at_fpr(rows: &[Row], start: [f64; FEATURES], target_fpr: f64) -> [f64; FEATURES] {
+    let objective = |weights: &[f64; FEATURES]| {
+        let samples = scores(rows, weights);
+        (eval::threshold_at_fpr(&samples, target_fpr).1.recall(), -log_loss(&samples))
+    };
+
+    let mut weights = project_to_simplex(&start);
+    let mut best = objective(&weights);
+    for step in TRANSFER_STEPS {
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for from in 0..FEATURES {
+                for to in (0..FEATURES).filter(|&to| to != from) {
+                    let amount = step.min(weights[from]);
+                    if amount <= 0.0 {
+                        continue;
+                    }
+                    let mut candidate = weights;
+                    candidate[from] -= amount;
+                    candidate[to] += amount;
+                    let value = objective(&candidate);
+                    if value > best {
+                        weights = candidate;
+                        best = value;
+                        improved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    weights
+}
+
+fn to_weights(values: &[f64; FEATURES]) -> FeatureWeights {
+    let rounded = values.map(|value| (value * 10_000.0).round() / 10_000.0);
+    FeatureWeights {
+        speed: rounded[0],
+        variability: rounded[1],
+        entropy: rounded[2],
+        backspace: rounded[3],
+        jitter: rounded[4],
+        iqr: rounded[5],
+        outliers: rounded[6],
+        paste: rounded[7],
+    }
+}
+
+/// Fit heuristic feature weights and the suspicious threshold on a labeled dataset
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let records = dataset::load(args.required("data")?)?;
+    let target_fpr = eval::target_fpr(args)?;
+    let objective = match args.get("objective").unwrap_or("log-loss") {
+        "log-loss" => Objective::LogLoss,
+        "recall-at-fpr" => Objective::RecallAtFpr,
+        other => return Err(format!("Unknown objective: {}", other).into()),
+    };
+
+    let humans = records.iter().filter(|record| record.is_human()).count();
+    if humans == 0 || humans == records.len() {
+        return Err("Dataset needs both human and bot records".into());
+    }
+
+    let (config, _) = cli::load_pipeline()?;
+    let curves = &config.scoring.normalization;
+    let rows: Vec<Row> = records
+        .iter()
+        .map(|record| {
+            let sub_scores = record.payload.features.sub_scores(curves);
+            (sub_scores.map(|(_, score)| score), record.is_human())
+        })
+        .collect();
+
+    let start = config.scoring.feature_weights.entries().map(|(_, weight)| weight);
+    let fitted = match objective {
+        Objective::LogLoss => fit_log_loss(&rows, start),
+        Objective::RecallAtFpr => fit_recall_at_fpr(&rows, start, target_fpr),
+    };
+    let before = scores(&rows, &start);
+    let after = scores(&rows, &fitted);
+    let (threshold, confusion) = eval::threshold_at_fpr(&after, target_fpr);
+
+    eprintln!("Records:      {}", rows.len());
+    eprintln!("Log-loss:     configured {:.4}, fitted {:.4}", log_loss(&before), log_loss(&after));
+    eprintln!("ROC AUC:      configured {:.4}, fitted {:.4}", eval::roc_auc(&before), eval::roc_auc(&after));
+    eprintln!(
+        "Recall at {:.2}% FPR: configured {:.4}, fitted {:.4}",
+        target_fpr * 100.0,
+        eval::threshold_at_fpr(&before, target_fpr).1.recall(),
+        confusion.recall()
+    );
+
+    let mut block = String::new();
+    writeln!(block, "[scoring]")?;
+    writeln!(block, "engine = \"heuristic\"")?;
+    writeln!(block, "suspicious_threshold = {:.4}", threshold)?;
+    writeln!(block)?;
+    write!(block, "[scoring.feature_weights]")?;
+    for (name, weight) in to_weights(&fitted).entries() {
+        write!(block, "\n{} = {:.4}", name, weight)?;
+    }
+
+    cli::write_output(args, &block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Humans and bots separated by the jitter sub-score only, the rest is noise
+    fn rows() -> Vec<Row> {
+        (0..200)
+            .map(|i| {
+                let human = i % 2 == 0;
+                let noise = |k: usize| ((i * 7 + k * 13) % 10) as f64 / 10.0;
+                let mut values: [f64; FEATURES] = std::array::from_fn(noise);
+                values[4] = if human { 0.9 } else { 0.1 };
+                (values, human)
+            })
+            .collect()
+    }
+
+    fn assert_on_simplex(weights: &[f64; FEATURES]) {
+        assert!(weights.iter().all(|&w| w >= 0.0));
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_project_to_simplex() {
+        let projected = project_to_simplex(&[0.5, 0.5, 0.5, 0.5, 0.0, 0.0, 0.0, -1.0]);
+        assert_on_simplex(&projected);
+        assert_eq!(projected, [0.25, 0.25, 0.25, 0.25, 0.0, 0.0, 0.0, 0.0]);
+
+        let inside = [0.125; FEATURES];
+        assert_eq!(project_to_simplex(&inside), inside);
+    }
+
+    #[test]
+    fn test_fit_log_loss_finds_separating_feature() {
+        let rows = rows();
+        let start = FeatureWeights::default().entries().map(|(_, weight)| weight);
+        let fitted = fit_log_loss(&rows, start);
+
+        assert_on_simplex(&fitted);
+        assert!(fitted[4] > 0.9, "{:?}", fitted);
+        assert!(log_loss(&scores(&rows, &fitted)) < log_loss(&scores(&rows, &start)));
+    }
+
+    #[test]
+    fn test_fit_recall_at_fpr() {
+        let rows = rows();
+        let start = FeatureWeights::default().entries().map(|(_, weight)| weight);
+        let fitted = fit_recall_at_fpr(&rows, start, 0.01);
+
+        assert_on_simplex(&fitted);
+        let (_, confusion) = eval::threshold_at_fpr(&scores(&rows, &fitted), 0.01);
+        assert_eq!(confusion.recall(), 1.0);
+        assert!(to_weights(&fitted).validate().is_ok());
+    }
+}
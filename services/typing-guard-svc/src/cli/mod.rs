@@ -0,0 +1,95 @@
+use std::{collections::HashMap, error::Error};
+
+use crate::{config::AppConfig, scoring::ScoringPipeline};
+
+pub mod calibrate;
+pub mod eval;
+pub mod fit;
+pub mod simulate;
+pub mod verify_token;
+
+const USAGE: &str = "\
+Usage: typing-guard-svc [COMMAND]
+
+Commands:
+  serve        Run the HTTP service (default)
+  calibrate    Fit a calibration from a labeled dataset
+                 --data <labeled.jsonl|labeled.csv> [--method platt|isotonic] [--out <file.json>]
+  eval         Report detection metrics of the configured scoring on a labeled dataset
+                 --data <labeled.jsonl|labeled.csv> [--target-fpr 0.01] [--out <report.txt>]
+  fit          Fit heuristic feature weights and the suspicious threshold
+                 --data <labeled.jsonl|labeled.csv> [--objective log-loss|recall-at-fpr]
+                 [--target-fpr 0.01] [--out <scoring.toml>]
+  simulate     Generate a labeled dataset of simulated humans and bots
+                 [--humans 500] [--bots 500] [--events 60] [--seed <n>] [--out <labeled.jsonl>]
+  verify-token Check an attestation token from /score and print its claims
+                 --token <jwt> [--out <claims.json>]";
+
+/// Run a CLI subcommand
+pub fn run(command: &str, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let args = Args::parse(args)?;
+    match command {
+        "calibrate" => calibrate::run(&args),
+        "eval" => eval::run(&args),
+        "fit" => fit::run(&args),
+        "simulate" => simulate::run(&args),
+        "verify-token" => verify_token::run(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("Unknown command: {}\n\n{}", other, USAGE).into()),
+    }
+}
+
+/// `--name value` options of a subcommand
+pub struct Args {
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument: {}\n\n{}", arg, USAGE))?;
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Missing value for --{}", name))?;
+            options.insert(name.to_string(), value.clone());
+        }
+        Ok(Self { options })
+    }
+
+    /// Value of an option, if given
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /// Value of an option that must be given
+    pub fn required(&self, name: &str) -> Result<&str, String> {
+        self.get(name).ok_or_else(|| format!("Missing required option --{}\n\n{}", name, USAGE))
+    }
+}
+
+/// Load and validate the service config, and build its scoring pipeline
+pub fn load_pipeline() -> Result<(AppConfig, ScoringPipeline), Box<dyn Error>> {
+    let config = AppConfig::load()?;
+    config.validate()?;
+    let pipeline = ScoringPipeline::new(&config.scoring)?;
+    Ok((config, pipeline))
+}
+
+/// Write command output to `--out`, or stdout when not given
+pub fn write_output(args: &Args, contents: &str) -> Result<(), Box<dyn Error>> {
+    match args.get("out") {
+        Some(path) => {
+            std::fs::write(path, contents)?;
+            eprintln!("Wrote {}", path);
+        }
+        None => println!("{}", contents),
+    }
+    Ok(())
+}
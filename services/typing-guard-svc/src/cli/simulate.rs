@@ -0,0 +1,37 @@
+use std::error::Error;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    cli::{self, Args},
+    simulation::Typist,
+};
+
+/// Write a labeled dataset of simulated humans and bots as JSONL
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let count = |name: &str, default: usize| -> Result<usize, String> {
+        args.get(name)
+            .map_or(Ok(default), |value| value.parse())
+            .map_err(|_| format!("--{} must be a non-negative integer", name))
+    };
+    let humans = count("humans", 500)?;
+    let bots = count("bots", 500)?;
+    let events = count("events", 60)?;
+    if events < 2 {
+        return Err("--events must be at least 2".into());
+    }
+
+    // Same seed, same dataset
+    let mut rng = match args.get("seed") {
+        Some(seed) => StdRng::seed_from_u64(seed.parse().map_err(|_| "--seed must be a non-negative integer")?),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut lines = Vec::with_capacity(humans + bots);
+    for i in 0..humans + bots {
+        let typist = if i < humans { Typist::random_human(&mut rng) } else { Typist::random_bot(&mut rng) };
+        lines.push(serde_json::to_string(&typist.labeled_payload(events, &mut rng))?);
+    }
+
+    cli::write_output(args, &lines.join("\n"))
+}
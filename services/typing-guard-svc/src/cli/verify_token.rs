@@ -0,0 +1,19 @@
+use std::error::Error;
+
+use crate::{
+    attestation,
+    cli::{self, Args},
+    config::AppConfig,
+};
+
+/// Check an attestation token against the configured key and print its claims
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let token = args.required("token")?;
+    let config = AppConfig::load()?;
+    if config.attestation.key.is_empty() {
+        return Err("No attestation key configured (attestation.key or ATTESTATION_KEY)".into());
+    }
+
+    let claims = attestation::verify(token, config.attestation.key.as_bytes())?;
+    cli::write_output(args, &serde_json::to_string_pretty(&claims)?)
+}
@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::env;
+use std::{env, fmt};
 
-use crate::models::features::FeatureWeights;
+use crate::{
+    models::{
+        features::{FeatureWeights, NormalizationCurves},
+        response::DecisionBand,
+    },
+    scoring::{ensemble::EnsembleConfig, experiment::ExperimentConfig, rules::Rule},
+};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +23,190 @@ pub struct AppConfig {
     
     /// Rate limiting configuration
     pub rate_limit: RateLimitConfig,
+    
+    /// A/B experiment assigning sessions to scoring configs by `session_id`
+    #[serde(default)]
+    pub experiment: Option<ExperimentConfig>,
+    
+    /// Candidate scoring configs run next to the live one without affecting responses
+    #[serde(default)]
+    pub shadows: Vec<ShadowConfig>,
+    
+    /// Per-session history across scoring windows
+    #[serde(default)]
+    pub session: SessionConfig,
+    
+    /// Detection of feature vectors replayed across sessions
+    #[serde(default)]
+    pub replay: ReplayConfig,
+    
+    /// Signed tokens of the verdict returned with every score
+    #[serde(default)]
+    pub attestation: AttestationConfig,
+}
+
+/// How verdicts are signed into tokens for a backend to verify
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttestationConfig {
+    /// Add an `attestation` token to score responses
+    pub enabled: bool,
+    
+    /// HS256 key shared with the backends verifying tokens, at least 32 bytes
+    pub key: String,
+    
+    /// Seconds a token stays valid after it was issued
+    pub ttl_secs: u64,
+}
+
+impl Default for AttestationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key: String::new(),
+            ttl_secs: 300,
+        }
+    }
+}
+
+// Written by hand so the key never reaches the logs
+impl fmt::Debug for AttestationConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttestationConfig")
+            .field("enabled", &self.enabled)
+            .field("key", &redacted(&self.key))
+            .field("ttl_secs", &self.ttl_secs)
+            .finish()
+    }
+}
+
+/// Stand-in for a secret in debug output
+fn redacted(secret: &str) -> &'static str {
+    if secret.is_empty() {
+        "<unset>"
+    } else {
+        "<redacted>"
+    }
+}
+
+impl AttestationConfig {
+    /// Check the key length and the token lifetime
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        // HS256 keys must be at least as long as the hash (RFC 7518, section 3.2)
+        if self.key.len() < 32 {
+            return Err("Attestation key must be at least 32 bytes".to_string());
+        }
+        if self.ttl_secs == 0 {
+            return Err("Attestation ttl_secs must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// How recently seen feature vectors are indexed to catch replays
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayConfig {
+    /// Index feature vectors and flag repeats from other sessions; off by default
+    pub enabled: bool,
+    
+    /// Seconds a feature vector stays in the index after it was last seen
+    pub window_secs: u64,
+    
+    /// Feature vectors indexed at once, the oldest are dropped beyond this
+    pub max_entries: usize,
+    
+    /// Significant digits kept per feature, fewer catches looser near-duplicates
+    pub significant_digits: u32,
+    
+    /// Subtracted from the score of a replayed submission
+    pub penalty: f64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 600,
+            max_entries: 100_000,
+            significant_digits: 2,
+            penalty: 0.3,
+        }
+    }
+}
+
+impl ReplayConfig {
+    /// Check the limits, precision and penalty
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_secs == 0 || self.max_entries == 0 {
+            return Err("Replay window_secs and max_entries must be greater than 0".to_string());
+        }
+        if !(1..=6).contains(&self.significant_digits) {
+            return Err("Replay significant_digits must be between 1 and 6".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.penalty) {
+            return Err("Replay penalty must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// How scoring windows of the same `session_id` are remembered and combined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Track sessions and add a session-level score to responses; off by default
+    pub enabled: bool,
+    
+    /// Seconds without a new window after which a session is forgotten
+    pub ttl_secs: u64,
+    
+    /// Most recent windows kept per session
+    pub max_windows: usize,
+    
+    /// Weight of the newest window in the session score (EWMA smoothing factor)
+    pub smoothing: f64,
+    
+    /// Sessions tracked at once, the least recently seen is dropped beyond this
+    pub max_sessions: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 1800,
+            max_windows: 20,
+            smoothing: 0.3,
+            max_sessions: 100_000,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Check the limits and the smoothing factor
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ttl_secs == 0 || self.max_windows == 0 || self.max_sessions == 0 {
+            return Err("Session ttl_secs, max_windows and max_sessions must be greater than 0".to_string());
+        }
+        if !(self.smoothing > 0.0 && self.smoothing <= 1.0) {
+            return Err("Session smoothing must be in (0.0, 1.0]".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A candidate scoring configuration evaluated in shadow mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowConfig {
+    /// Name used in logs and counters
+    pub name: String,
+    
+    /// Complete scoring settings of the candidate
+    pub scoring: ScoringConfig,
 }
 
 /// Server configuration
@@ -33,23 +223,198 @@ pub struct ServerConfig {
 }
 
 /// Security configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     /// HMAC key for request signing
     pub hmac_key: String,
     
     /// Whether to require HMAC signatures
     pub require_hmac: bool,
+    
+    /// Largest accepted distance between a payload's `ts` and the server clock, 0 disables the check
+    #[serde(default = "default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: u64,
+    
+    /// Reject a nonce, or (`session_id`, `ts`) pair, seen while its timestamp is still fresh
+    #[serde(default = "default_reject_duplicates")]
+    pub reject_duplicates: bool,
+    
+    /// Fresh requests and unexpired challenges remembered for duplicate detection; beyond this, new ones are
+    /// refused until some expire, so allow for the peak request rate over twice the clock skew
+    #[serde(default = "default_duplicate_cache_size")]
+    pub duplicate_cache_size: usize,
+    
+    /// When the duplicate cache is full, accept new requests and challenges without remembering
+    /// them instead of answering 503; keeps serving under a flood, at the cost of replays meanwhile
+    #[serde(default)]
+    pub fail_open_when_full: bool,
+    
+    /// Whether payloads must carry a challenge from `GET /challenge`
+    #[serde(default)]
+    pub require_challenge: bool,
+    
+    /// How long an issued challenge can be redeemed
+    #[serde(default = "default_challenge_ttl_secs")]
+    pub challenge_ttl_secs: u64,
+    
+    /// Key signing challenges, shared by every instance; a random per-process key when unset
+    #[serde(default)]
+    pub challenge_key: Option<String>,
+}
+
+// Written by hand so the keys never reach the logs
+impl fmt::Debug for SecurityConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecurityConfig")
+            .field("hmac_key", &redacted(&self.hmac_key))
+            .field("require_hmac", &self.require_hmac)
+            .field("max_clock_skew_secs", &self.max_clock_skew_secs)
+            .field("reject_duplicates", &self.reject_duplicates)
+            .field("duplicate_cache_size", &self.duplicate_cache_size)
+            .field("fail_open_when_full", &self.fail_open_when_full)
+            .field("require_challenge", &self.require_challenge)
+            .field("challenge_ttl_secs", &self.challenge_ttl_secs)
+            .field("challenge_key", &self.challenge_key.as_deref().map(redacted))
+            .finish()
+    }
+}
+
+fn default_max_clock_skew_secs() -> u64 {
+    300
+}
+
+fn default_reject_duplicates() -> bool {
+    true
+}
+
+fn default_duplicate_cache_size() -> usize {
+    100_000
+}
+
+fn default_challenge_ttl_secs() -> u64 {
+    120
 }
 
 /// Scoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoringConfig {
+    /// Scoring engine to use (see `scoring::ENGINES`)
+    #[serde(default = "default_engine")]
+    pub engine: String,
+
+    /// Model file (JSON or TOML) for model-based engines
+    #[serde(default)]
+    pub model_path: Option<String>,
+
+    /// Calibration parameter file (JSON or TOML) applied after the scorer
+    #[serde(default)]
+    pub calibration_path: Option<String>,
+
     /// Default threshold for suspicious behavior
     pub suspicious_threshold: f64,
     
+    /// Decision bands, highest first; derived from `suspicious_threshold` when empty
+    #[serde(default)]
+    pub bands: Vec<DecisionBand>,
+    
+    /// Windows with fewer events are labeled `insufficient_data` instead of getting a verdict
+    #[serde(default = "default_min_events")]
+    pub min_events: u32,
+    
     /// Feature weights for scoring
     pub feature_weights: FeatureWeights,
+
+    /// Normalization curves for the heuristic engine
+    #[serde(default)]
+    pub normalization: NormalizationCurves,
+
+    /// Members and combiner, for the `ensemble` engine
+    #[serde(default)]
+    pub ensemble: Option<EnsembleConfig>,
+
+    /// Reference human population for anomaly scoring
+    #[serde(default)]
+    pub anomaly: Option<AnomalyConfig>,
+
+    /// Cross-feature consistency checks against forged aggregates
+    #[serde(default)]
+    pub consistency: ConsistencyConfig,
+
+    /// Hard rules evaluated around the scorer
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+
+    /// Platform/locale profiles, first match wins, the settings above are the fallback
+    #[serde(default)]
+    pub profiles: Vec<ProfileConfig>,
+}
+
+/// Mahalanobis distance from a reference human population
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// Population file (JSON or TOML) with a mean vector and covariance matrix
+    pub population_path: String,
+
+    /// Share of the final score taken from the anomaly score, 0.0 only reports it
+    #[serde(default)]
+    pub weight: f64,
+}
+
+/// What to do with features that fail the consistency checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsistencyConfig {
+    /// Run the checks and add the `inconsistent_features` hint
+    pub enabled: bool,
+
+    /// Also replace the label with the first band that isn't allowed
+    pub force_suspicious: bool,
+
+    /// Share of intervals that may be zero-length and so left out of `iki_mean`, for the window check.
+    /// Millisecond timestamps of distinct keys almost never coincide, so none are assumed by default;
+    /// raise it for clients whose coarse timers stamp consecutive keys alike.
+    pub max_zero_interval_share: f64,
+}
+
+impl Default for ConsistencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            force_suspicious: false,
+            max_zero_interval_share: 0.0,
+        }
+    }
+}
+
+/// Scoring profile selected by `TypingMeta.platform` and `TypingMeta.locale`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Profile name, echoed in score responses
+    pub name: String,
+
+    /// Platform patterns (`*` wildcard, case-insensitive), empty matches any
+    #[serde(default)]
+    pub platforms: Vec<String>,
+
+    /// Locale patterns (`*` wildcard, `-` and `_` interchangeable), empty matches any
+    #[serde(default)]
+    pub locales: Vec<String>,
+
+    /// Threshold override, defaults to `scoring.suspicious_threshold`
+    #[serde(default)]
+    pub suspicious_threshold: Option<f64>,
+
+    /// Band override, defaults to `scoring.bands` (or this profile's threshold when those are empty)
+    #[serde(default)]
+    pub bands: Option<Vec<DecisionBand>>,
+
+    /// Weight override, defaults to `scoring.feature_weights`
+    #[serde(default)]
+    pub feature_weights: Option<FeatureWeights>,
+
+    /// Normalization override, defaults to `scoring.normalization`
+    #[serde(default)]
+    pub normalization: Option<NormalizationCurves>,
 }
 
 /// Rate limiting configuration
@@ -69,6 +434,11 @@ impl Default for AppConfig {
             security: SecurityConfig::default(),
             scoring: ScoringConfig::default(),
             rate_limit: RateLimitConfig::default(),
+            experiment: None,
+            shadows: Vec::new(),
+            session: SessionConfig::default(),
+            replay: ReplayConfig::default(),
+            attestation: AttestationConfig::default(),
         }
     }
 }
@@ -88,15 +458,41 @@ impl Default for SecurityConfig {
         Self {
             hmac_key: "default-key-change-in-production".to_string(),
             require_hmac: false,
+            max_clock_skew_secs: default_max_clock_skew_secs(),
+            reject_duplicates: default_reject_duplicates(),
+            duplicate_cache_size: default_duplicate_cache_size(),
+            fail_open_when_full: false,
+            require_challenge: false,
+            challenge_ttl_secs: default_challenge_ttl_secs(),
+            challenge_key: None,
         }
     }
 }
 
+fn default_engine() -> String {
+    crate::scoring::HeuristicScorer::NAME.to_string()
+}
+
+fn default_min_events() -> u32 {
+    10
+}
+
 impl Default for ScoringConfig {
     fn default() -> Self {
         Self {
+            engine: default_engine(),
+            model_path: None,
+            calibration_path: None,
             suspicious_threshold: 0.4,
+            bands: Vec::new(),
+            min_events: default_min_events(),
             feature_weights: FeatureWeights::default(),
+            normalization: NormalizationCurves::default(),
+            ensemble: None,
+            anomaly: None,
+            consistency: ConsistencyConfig::default(),
+            rules: Vec::new(),
+            profiles: Vec::new(),
         }
     }
 }
@@ -131,6 +527,21 @@ impl AppConfig {
         if let Ok(require_hmac) = env::var("REQUIRE_HMAC") {
             settings = settings.set_override("security.require_hmac", require_hmac.parse::<bool>().unwrap_or(false))?;
         }
+        if let Ok(challenge_key) = env::var("CHALLENGE_KEY") {
+            settings = settings.set_override("security.challenge_key", challenge_key)?;
+        }
+        if let Ok(attestation_key) = env::var("ATTESTATION_KEY") {
+            settings = settings.set_override("attestation.key", attestation_key)?;
+        }
+        if let Ok(engine) = env::var("SCORING_ENGINE") {
+            settings = settings.set_override("scoring.engine", engine)?;
+        }
+        if let Ok(model_path) = env::var("SCORING_MODEL_PATH") {
+            settings = settings.set_override("scoring.model_path", model_path)?;
+        }
+        if let Ok(calibration_path) = env::var("SCORING_CALIBRATION_PATH") {
+            settings = settings.set_override("scoring.calibration_path", calibration_path)?;
+        }
         if let Ok(threshold) = env::var("SUSPICIOUS_THRESHOLD") {
             settings = settings.set_override("scoring.suspicious_threshold", threshold.parse::<f64>().unwrap_or(0.4))?;
         }
@@ -149,11 +560,37 @@ impl AppConfig {
             return Err("HMAC key cannot be empty".to_string());
         }
         
-        if self.scoring.suspicious_threshold < 0.0 || self.scoring.suspicious_threshold > 1.0 {
-            return Err("Suspicious threshold must be between 0.0 and 1.0".to_string());
+        if self.security.duplicate_cache_size == 0 {
+            return Err("Duplicate cache size must be greater than 0".to_string());
         }
         
-        self.scoring.feature_weights.validate()?;
+        if self.security.challenge_ttl_secs == 0 {
+            return Err("Challenge TTL must be greater than 0".to_string());
+        }
+        
+        if self.security.challenge_key.as_ref().is_some_and(|key| key.is_empty()) {
+            return Err("Challenge key cannot be empty".to_string());
+        }
+        
+        self.scoring.validate()?;
+        
+        if let Some(experiment) = &self.experiment {
+            experiment.validate()?;
+        }
+        
+        for (i, shadow) in self.shadows.iter().enumerate() {
+            if shadow.name.is_empty() {
+                return Err("Shadow name cannot be empty".to_string());
+            }
+            if self.shadows[..i].iter().any(|other| other.name == shadow.name) {
+                return Err(format!("Duplicate shadow name: {}", shadow.name));
+            }
+            shadow.scoring.validate().map_err(|e| format!("Shadow '{}': {}", shadow.name, e))?;
+        }
+        
+        self.session.validate()?;
+        self.replay.validate()?;
+        self.attestation.validate()?;
         
         if self.rate_limit.requests_per_minute == 0 {
             return Err("Rate limit requests per minute must be greater than 0".to_string());
@@ -162,3 +599,229 @@ impl AppConfig {
         Ok(())
     }
 }
+
+impl ScoringConfig {
+    /// Validate scoring settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.suspicious_threshold < 0.0 || self.suspicious_threshold > 1.0 {
+            return Err("Suspicious threshold must be between 0.0 and 1.0".to_string());
+        }
+        
+        if !crate::scoring::is_known_engine(&self.engine) {
+            return Err(format!("Unknown scoring engine: {}", self.engine));
+        }
+        
+        if !self.bands.is_empty() {
+            DecisionBand::validate_all(&self.bands).map_err(|e| format!("scoring.bands: {}", e))?;
+        }
+        
+        self.feature_weights.validate()?;
+        self.normalization.validate().map_err(|e| format!("scoring.normalization: {}", e))?;
+        
+        match &self.ensemble {
+            Some(ensemble) => ensemble.validate().map_err(|e| format!("scoring.ensemble: {}", e))?,
+            None if self.engine == crate::scoring::EnsembleScorer::NAME => {
+                return Err("Scoring engine 'ensemble' needs a [scoring.ensemble] section".to_string());
+            }
+            None => {}
+        }
+        
+        if !(0.0..1.0).contains(&self.consistency.max_zero_interval_share) {
+            return Err("Consistency max_zero_interval_share must be at least 0.0 and below 1.0".to_string());
+        }
+        
+        if let Some(anomaly) = &self.anomaly {
+            if !(0.0..=1.0).contains(&anomaly.weight) {
+                return Err("Anomaly weight must be between 0.0 and 1.0".to_string());
+            }
+        }
+        
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate()?;
+            if self.rules[..i].iter().any(|other| other.id == rule.id) {
+                return Err(format!("Duplicate rule id: {}", rule.id));
+            }
+        }
+        
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if profile.name.is_empty() || profile.name == crate::scoring::pipeline::DEFAULT_PROFILE {
+                return Err(format!("Profile name '{}' is empty or reserved", profile.name));
+            }
+            if self.profiles[..i].iter().any(|other| other.name == profile.name) {
+                return Err(format!("Duplicate profile name: {}", profile.name));
+            }
+            if let Some(threshold) = profile.suspicious_threshold {
+                if !(0.0..=1.0).contains(&threshold) {
+                    return Err(format!("Suspicious threshold of profile '{}' must be between 0.0 and 1.0", profile.name));
+                }
+            }
+            if let Some(bands) = &profile.bands {
+                DecisionBand::validate_all(bands).map_err(|e| format!("Profile '{}': {}", profile.name, e))?;
+            }
+            // Other engines, ensembles included, score with the `[scoring]` weights and curves
+            let overrides_heuristic = profile.feature_weights.is_some() || profile.normalization.is_some();
+            if overrides_heuristic && self.engine != crate::scoring::HeuristicScorer::NAME {
+                return Err(format!(
+                    "Profile '{}' overrides weights or curves, which only the heuristic engine uses",
+                    profile.name
+                ));
+            }
+            if let Some(weights) = &profile.feature_weights {
+                weights.validate().map_err(|e| format!("Profile '{}': {}", profile.name, e))?;
+            }
+            if let Some(normalization) = &profile.normalization {
+                normalization.validate().map_err(|e| format!("Profile '{}': {}", profile.name, e))?;
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::features::Curve;
+
+    fn parse(toml: &str) -> AppConfig {
+        let defaults = toml::to_string(&AppConfig::default()).unwrap();
+        config::Config::builder()
+            .add_source(config::File::from_str(&defaults, config::FileFormat::Toml))
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(AppConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_shipped_config_is_valid() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/default.toml");
+        let config: AppConfig = config::Config::builder()
+            .add_source(config::File::with_name(path))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        assert!(config.validate().is_ok());
+        // Deployments without their own curves score the same with or without the file
+        assert_eq!(config.scoring.normalization, NormalizationCurves::default());
+    }
+
+    #[test]
+    fn test_curves_from_toml() {
+        let config = parse(
+            r#"
+            [scoring.normalization.speed]
+            type = "piecewise"
+            points = [[50.0, 0.0], [100.0, 1.0], [300.0, 1.0], [1000.0, 0.0]]
+
+            [scoring.normalization.jitter]
+            type = "trapezoid"
+            lower_cutoff = 3.0
+            plateau_start = 8.0
+            plateau_end = 60.0
+            upper_cutoff = 120.0
+            lower_floor = 0.2
+            "#,
+        );
+
+        assert!(config.validate().is_ok());
+        assert!(matches!(config.scoring.normalization.speed, Curve::Piecewise { ref points } if points.len() == 4));
+        assert_eq!(
+            config.scoring.normalization.jitter,
+            Curve::trapezoid(3.0, (8.0, 60.0), 120.0, (0.2, 0.0))
+        );
+        assert_eq!(config.scoring.normalization.entropy, NormalizationCurves::default().entropy);
+    }
+
+    #[test]
+    fn test_bands_from_toml() {
+        let config = parse(
+            r#"
+            [[scoring.bands]]
+            name = "human"
+            min_score = 0.6
+            action = "allow"
+
+            [[scoring.bands]]
+            name = "bot"
+            min_score = 0.0
+            action = "block"
+            "#,
+        );
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.scoring.bands.len(), 2);
+        assert_eq!(config.scoring.bands[1].action, crate::models::response::Action::Block);
+    }
+
+    #[test]
+    fn test_shadow_config_validated() {
+        let mut config = AppConfig::default();
+        config.shadows.push(ShadowConfig {
+            name: "stricter".to_string(),
+            scoring: ScoringConfig {
+                suspicious_threshold: 0.5,
+                ..ScoringConfig::default()
+            },
+        });
+        assert!(config.validate().is_ok());
+
+        config.shadows[0].scoring.suspicious_threshold = 1.5;
+        assert!(config.validate().unwrap_err().starts_with("Shadow 'stricter'"));
+    }
+
+    #[test]
+    fn test_profile_curves_need_heuristic_engine() {
+        let profile = r#"
+            [[scoring.profiles]]
+            name = "cjk_ime"
+            locales = ["zh*"]
+
+            [scoring.profiles.normalization.speed]
+            type = "trapezoid"
+            lower_cutoff = 80.0
+            plateau_start = 150.0
+            plateau_end = 600.0
+            upper_cutoff = 1500.0
+            "#;
+        let mut config = parse(profile);
+        assert!(config.validate().is_ok());
+
+        config.scoring.engine = "logistic".to_string();
+        assert!(config.validate().unwrap_err().starts_with("Profile 'cjk_ime' overrides"));
+    }
+
+    #[test]
+    fn test_debug_output_hides_secrets() {
+        let mut config = AppConfig::default();
+        config.attestation.key = "attestation-secret-of-32-bytes!!".to_string();
+        config.security.challenge_key = Some("challenge-secret".to_string());
+
+        let logged = format!("{:?}", config);
+        assert!(!logged.contains("attestation-secret"));
+        assert!(!logged.contains("challenge-secret"));
+        assert!(!logged.contains(&config.security.hmac_key));
+        assert!(logged.contains("<redacted>"));
+    }
+
+    #[test]
+    fn test_invalid_curve_rejected() {
+        let config = parse(
+            r#"
+            [scoring.normalization.entropy]
+            type = "piecewise"
+            points = [[1.0, 0.0]]
+            "#,
+        );
+
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::models::{
+    features::TypingFeatures,
+    payload::{KeyEvent, KeyEventKind},
+};
+
+/// Intervals at or above this many milliseconds are pauses, not typing
+const MAX_INTERVAL_MS: f64 = 10_000.0;
+
+/// Number of histogram bins used for the interval entropy
+const ENTROPY_BINS: usize = 10;
+
+/// Compute the aggregate features of a window of key events, using the same
+/// formulas as the Dart `TypingAnalyzer`
+pub fn extract_features(events: &[KeyEvent]) -> TypingFeatures {
+    let paste_events = events.iter().filter(|e| e.kind == KeyEventKind::Paste).count();
+    let ikis = inter_key_intervals(events);
+    // Pastes still count without intervals, so a window of only pastes scores as one
+    if events.len() < 2 || ikis.is_empty() {
+        return TypingFeatures {
+            events: events.len() as u32,
+            iki_mean: 0.0,
+            iki_std: 0.0,
+            iki_iqr: 0.0,
+            burstiness: 0.0,
+            entropy: 0.0,
+            backspace_per_100: 0.0,
+            paste_events: paste_events as u32,
+            jitter_mad: 0.0,
+            outlier_ratio: 0.0,
+        };
+    }
+
+    let keystrokes = events.len() - paste_events;
+    let backspaces = events.iter().filter(|e| e.kind == KeyEventKind::Backspace).count();
+    let backspace_per_100 = if keystrokes > 0 {
+        backspaces as f64 / keystrokes as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let iki_mean = mean(&ikis);
+    let iki_std = standard_deviation(&ikis, iki_mean);
+    let burstiness = if iki_std > 0.0 {
+        (iki_std - iki_mean) / (iki_std + iki_mean)
+    } else {
+        0.0
+    };
+    let outliers = ikis.iter().filter(|iki| (*iki - iki_mean).abs() > 3.0 * iki_std).count();
+
+    TypingFeatures {
+        events: events.len() as u32,
+        iki_mean,
+        iki_std,
+        iki_iqr: interquartile_range(&ikis),
+        burstiness,
+        entropy: entropy(&ikis),
+        backspace_per_100,
+        paste_events: paste_events as u32,
+        jitter_mad: jitter_mad(&ikis),
+        outlier_ratio: outliers as f64 / ikis.len() as f64,
+    }
+}
+
+/// Intervals between consecutive non-paste events, dropping zero and pause-length gaps
+fn inter_key_intervals(events: &[KeyEvent]) -> Vec<f64> {
+    let keystrokes: Vec<f64> = events
+        .iter()
+        .filter(|e| e.kind != KeyEventKind::Paste)
+        .map(|e| e.t as f64)
+        .collect();
+
+    keystrokes
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|interval| *interval > 0.0 && *interval < MAX_INTERVAL_MS)
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Population standard deviation
+fn standard_deviation(values: &[f64], mean: f64) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+/// Q3 - Q1 using floor indices, zero below four values
+fn interquartile_range(values: &[f64]) -> f64 {
+    if values.len() < 4 {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let q1 = (sorted.len() as f64 * 0.25).floor() as usize;
+    let q3 = (sorted.len() as f64 * 0.75).floor() as usize;
+    sorted[q3] - sorted[q1]
+}
+
+/// Shannon entropy (bits) of a 10-bin histogram spanning min..max
+fn entropy(values: &[f64]) -> f64 {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if values.is_empty() || min == max {
+        return 0.0;
+    }
+
+    let bin_size = (max - min) / ENTROPY_BINS as f64;
+    let mut bins = [0usize; ENTROPY_BINS];
+    for value in values {
+        let bin = ((value - min) / bin_size).floor().clamp(0.0, (ENTROPY_BINS - 1) as f64);
+        bins[bin as usize] += 1;
+    }
+
+    bins.iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / values.len() as f64;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// Mean absolute deviation of the absolute differences between consecutive intervals
+fn jitter_mad(ikis: &[f64]) -> f64 {
+    if ikis.len() < 2 {
+        return 0.0;
+    }
+    let differences: Vec<f64> = ikis.windows(2).map(|pair| (pair[1] - pair[0]).abs()).collect();
+    let mean = mean(&differences);
+    differences.iter().map(|d| (d - mean).abs()).sum::<f64>() / differences.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(times: &[u64], kinds: &[KeyEventKind]) -> Vec<KeyEvent> {
+        times
+            .iter()
+            .zip(kinds.iter().chain(std::iter::repeat(&KeyEventKind::Char)))
+            .map(|(&t, &kind)| KeyEvent { t, kind })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_dart_formulas() {
+        // Intervals 100, 200, 100, 300, 100 ms
+        let features = extract_features(&events(&[0, 100, 300, 400, 700, 800], &[]));
+
+        assert_eq!(features.events, 6);
+        assert!((features.iki_mean - 160.0).abs() < 1e-9);
+        let std = (((60.0f64).powi(2) * 3.0 + 40.0f64.powi(2) + 140.0f64.powi(2)) / 5.0).sqrt();
+        assert!((features.iki_std - std).abs() < 1e-9);
+        assert!((features.burstiness - (std - 160.0) / (std + 160.0)).abs() < 1e-9);
+        // Sorted [100, 100, 100, 200, 300]: indices 1 and 3
+        assert!((features.iki_iqr - 100.0).abs() < 1e-9);
+        // Bins of 20 ms from 100: three in the first, one in the middle, one in the last
+        let expected_entropy = -(0.6f64 * 0.6f64.log2() + 2.0 * 0.2 * 0.2f64.log2());
+        assert!((features.entropy - expected_entropy).abs() < 1e-9);
+        // Differences 100, 100, 200, 200 -> mean 150, MAD 50
+        assert!((features.jitter_mad - 50.0).abs() < 1e-9);
+        assert_eq!(features.outlier_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_backspace_and_paste_counts() {
+        use KeyEventKind::*;
+        let features = extract_features(&events(
+            &[0, 150, 320, 400, 560, 700],
+            &[Char, Backspace, Char, Paste, Char, Char],
+        ));
+
+        assert_eq!(features.paste_events, 1);
+        // One backspace among five keystrokes, pastes excluded
+        assert!((features.backspace_per_100 - 20.0).abs() < 1e-9);
+        // Paste is skipped when measuring intervals: 150, 170, 240, 140
+        assert!((features.iki_mean - 175.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pauses_and_repeats_dropped() {
+        let features = extract_features(&events(&[0, 0, 20_000, 20_100], &[]));
+        assert!((features.iki_mean - 100.0).abs() < 1e-9);
+
+        let too_short = extract_features(&events(&[5], &[]));
+        assert_eq!(too_short.events, 1);
+        assert_eq!(too_short.iki_mean, 0.0);
+
+        let pasted = extract_features(&events(&[0, 400], &[KeyEventKind::Paste, KeyEventKind::Paste]));
+        assert_eq!((pasted.events, pasted.paste_events), (2, 2));
+        assert_eq!(pasted.iki_mean, 0.0);
+    }
+}
@@ -0,0 +1,18 @@
+use axum::{
+    extract::State,
+    response::Json,
+};
+use tracing::info;
+
+use crate::{
+    models::response::ChallengeResponse,
+    state::AppState,
+};
+
+/// Hand out a single-use challenge for the next scoring payload
+pub async fn challenge_handler(
+    State(state): State<AppState>,
+) -> Json<ChallengeResponse> {
+    info!("Challenge requested");
+    Json(state.challenges.issue())
+}
@@ -5,20 +5,23 @@ use axum::{
 use tracing::info;
 
 use crate::{
-    config::AppConfig,
-    models::response::{ConfigResponse, RateLimitConfig, Thresholds},
+    models::response::{ConfigResponse, RateLimitConfig},
+    state::AppState,
 };
 
 /// Configuration endpoint
 pub async fn config_handler(
-    State(config): State<AppConfig>,
+    State(state): State<AppState>,
 ) -> Json<ConfigResponse> {
     info!("Configuration requested");
     
+    let config = state.config;
     let response = ConfigResponse {
-        default_thresholds: Thresholds {
-            suspicious_below: config.scoring.suspicious_threshold,
-        },
+        engine: config.scoring.engine,
+        model_version: state.pipeline.default_profile().scorer.version().map(str::to_string),
+        calibration: state.pipeline.calibration().map(|c| c.method().to_string()),
+        min_events: config.scoring.min_events,
+        default_thresholds: state.pipeline.default_profile().thresholds.clone(),
         feature_weights: config.scoring.feature_weights,
         rate_limits: RateLimitConfig {
             requests_per_minute: config.rate_limit.requests_per_minute,
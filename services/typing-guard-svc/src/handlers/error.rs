@@ -0,0 +1,81 @@
+use axum::{
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+
+use crate::{
+    middleware::{challenge::ChallengeError, freshness::FreshnessError},
+    models::response::ErrorResponse,
+};
+
+/// An error response with its status and machine-readable code
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Payload failed validation
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "VALIDATION_ERROR", message)
+    }
+
+    /// Request signature missing or wrong
+    pub fn authentication(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "AUTHENTICATION_ERROR", message)
+    }
+}
+
+/// A body that isn't JSON, or doesn't have the payload's fields, fails validation like a bad value
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::validation(rejection.body_text())
+    }
+}
+
+impl From<FreshnessError> for ApiError {
+    fn from(error: FreshnessError) -> Self {
+        let (status, code) = match error {
+            FreshnessError::Stale(_) => (StatusCode::BAD_REQUEST, "TIMESTAMP_STALE"),
+            FreshnessError::InFuture(_) => (StatusCode::BAD_REQUEST, "TIMESTAMP_IN_FUTURE"),
+            FreshnessError::Duplicate => (StatusCode::CONFLICT, "DUPLICATE_REQUEST"),
+            FreshnessError::Full => (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE"),
+        };
+        Self::new(status, code, error.to_string())
+    }
+}
+
+impl From<ChallengeError> for ApiError {
+    fn from(error: ChallengeError) -> Self {
+        let (status, code) = match error {
+            ChallengeError::Missing => (StatusCode::UNAUTHORIZED, "CHALLENGE_REQUIRED"),
+            ChallengeError::Invalid => (StatusCode::UNAUTHORIZED, "CHALLENGE_INVALID"),
+            ChallengeError::Expired(_) => (StatusCode::UNAUTHORIZED, "CHALLENGE_EXPIRED"),
+            ChallengeError::Used => (StatusCode::CONFLICT, "CHALLENGE_USED"),
+            ChallengeError::Full => (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE"),
+        };
+        Self::new(status, code, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse {
+            error: self.status.canonical_reason().unwrap_or("Error").to_string(),
+            message: self.message,
+            code: self.code.to_string(),
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
@@ -1,7 +1,12 @@
+pub mod challenge;
+pub mod error;
 pub mod health;
 pub mod score;
 pub mod config;
+pub mod shadow;
 
+pub use challenge::*;
 pub use health::*;
 pub use score::*;
 pub use config::*;
+pub use shadow::*;
@@ -1,47 +1,149 @@
 use axum::{
-    extract::State,
-    http::{HeaderMap, StatusCode},
+    extract::{rejection::JsonRejection, State},
+    http::HeaderMap,
     response::Json,
     Json as AxumJson,
 };
 use tracing::{info, warn};
 
 use crate::{
-    config::AppConfig,
-    models::{payload::TypingFeaturePayload, response::ScoreResponse},
+    handlers::error::ApiError,
+    models::{
+        payload::{RawTypingPayload, TypingFeaturePayload},
+        response::ScoreResponse,
+    },
     middleware::hmac::verify_hmac_signature,
+    state::AppState,
 };
 
 /// The main endpoint - analyze typing features and give a score
 pub async fn score_handler(
-    State(config): State<AppConfig>,
+    State(state): State<AppState>,
     headers: HeaderMap,
-    AxumJson(payload): AxumJson<TypingFeaturePayload>,
-) -> Result<Json<ScoreResponse>, StatusCode> {
+    payload: Result<AxumJson<TypingFeaturePayload>, JsonRejection>,
+) -> Result<Json<ScoreResponse>, ApiError> {
+    let AxumJson(payload) = payload?;
+    let config = &state.config;
+
     // Check if the data looks valid
     if let Err(e) = payload.validate() {
         warn!("Invalid payload: {}", e);
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(ApiError::validation(e));
     }
 
     // Verify request signature if HMAC is enabled
     if config.security.require_hmac {
         if let Err(e) = verify_hmac_signature(&headers, &payload, &config.security.hmac_key) {
             warn!("HMAC verification failed: {}", e);
-            return Err(StatusCode::UNAUTHORIZED);
+            return Err(ApiError::authentication(e.to_string()));
         }
     }
 
-    // Do the actual scoring
-    let score = payload.features.calculate_score(&config.scoring.feature_weights);
-    
-    // Package up the response
-    let response = ScoreResponse::new(score, config.scoring.suspicious_threshold);
+    if let Err(e) = state.freshness.check(payload.ts) {
+        warn!("Rejected payload of session {}: {}", payload.session_id, e);
+        return Err(e.into());
+    }
+
+    if let Err(e) = state.challenges.redeem(payload.challenge.as_deref()) {
+        warn!("Rejected challenge of session {}: {}", payload.session_id, e);
+        return Err(e.into());
+    }
+
+    // Remembered only once every other check passed, so a rejected payload can be retried
+    if let Err(e) = state.freshness.record(payload.session_id, payload.ts, payload.nonce.as_deref()) {
+        warn!("Rejected payload of session {}: {}", payload.session_id, e);
+        return Err(e.into());
+    }
+
+    Ok(Json(evaluate(&state, &payload)))
+}
+
+/// Same as `score_handler`, but computes the features from raw key-event timings
+pub async fn score_raw_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    raw: Result<AxumJson<RawTypingPayload>, JsonRejection>,
+) -> Result<Json<ScoreResponse>, ApiError> {
+    let AxumJson(raw) = raw?;
+    let config = &state.config;
+
+    if let Err(e) = raw.validate() {
+        warn!("Invalid raw payload: {}", e);
+        return Err(ApiError::validation(e));
+    }
+
+    // The signature covers the payload as sent, not the derived features
+    if config.security.require_hmac {
+        if let Err(e) = verify_hmac_signature(&headers, &raw, &config.security.hmac_key) {
+            warn!("HMAC verification failed: {}", e);
+            return Err(ApiError::authentication(e.to_string()));
+        }
+    }
+
+    if let Err(e) = state.freshness.check(raw.ts) {
+        warn!("Rejected raw payload of session {}: {}", raw.session_id, e);
+        return Err(e.into());
+    }
+
+    let payload = raw.to_feature_payload();
+    if let Err(e) = payload.validate_extracted() {
+        warn!("Extracted features rejected: {}", e);
+        return Err(ApiError::validation(e));
+    }
+
+    if let Err(e) = state.challenges.redeem(raw.challenge.as_deref()) {
+        warn!("Rejected challenge of session {}: {}", raw.session_id, e);
+        return Err(e.into());
+    }
+
+    // Remembered only once every other check passed, so a rejected payload can be retried
+    if let Err(e) = state.freshness.record(raw.session_id, raw.ts, raw.nonce.as_deref()) {
+        warn!("Rejected raw payload of session {}: {}", raw.session_id, e);
+        return Err(e.into());
+    }
+
+    Ok(Json(evaluate(&state, &payload)))
+}
+
+/// Do the actual scoring
+fn evaluate(state: &AppState, payload: &TypingFeaturePayload) -> ScoreResponse {
+    let arm = state.experiment.as_ref().map(|experiment| (experiment, experiment.assign(&payload.session_id)));
+    let pipeline = arm.and_then(|(_, arm)| arm.pipeline.as_ref()).unwrap_or(&state.pipeline);
+    let mut response = pipeline.evaluate(payload);
+    if let Some((experiment, arm)) = arm {
+        response.experiment = Some(experiment.label(arm));
+    }
+
+    // Candidates only log and count, and are compared with the pipeline's own verdict,
+    // before the replay penalty and session tracking adjust it
+    for shadow in state.shadows.iter() {
+        shadow.observe(payload, &response);
+    }
+
+    if let Some(replay) = &state.replay {
+        if replay.check(payload.session_id, &payload.features) {
+            response.mark_replayed(replay.penalty);
+        }
+    }
+    if let Some(sessions) = &state.sessions {
+        response.session = Some(sessions.record(payload.session_id, &payload.features, &response));
+    }
+    // Signed last, so the token carries the final verdict
+    if let Some(attestor) = &state.attestor {
+        let scorer = &pipeline.select_profile(payload.meta.as_ref()).scorer;
+        response.attestation = Some(attestor.attest(payload.session_id, &response, scorer.as_ref()));
+    }
     
-    info!(
-        "Scored session {}: score={:.3}, label={}",
-        payload.session_id, score, response.label
-    );
+    match &response.experiment {
+        Some(arm) => info!(
+            "Scored session {} with profile {} in {}/{}: score={:.3}, label={}",
+            payload.session_id, response.profile, arm.experiment, arm.arm, response.score, response.label
+        ),
+        None => info!(
+            "Scored session {} with profile {}: score={:.3}, label={}",
+            payload.session_id, response.profile, response.score, response.label
+        ),
+    }
 
-    Ok(Json(response))
+    response
 }
@@ -0,0 +1,18 @@
+use axum::{
+    extract::State,
+    response::Json,
+};
+use tracing::info;
+
+use crate::{
+    scoring::shadow::ShadowStats,
+    state::AppState,
+};
+
+/// Shadow scoring counters endpoint
+pub async fn shadow_handler(
+    State(state): State<AppState>,
+) -> Json<Vec<ShadowStats>> {
+    info!("Shadow stats requested");
+    Json(state.shadows.iter().map(|shadow| shadow.stats()).collect())
+}
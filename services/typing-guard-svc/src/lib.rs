@@ -0,0 +1,95 @@
+use axum::{
+    http::Method,
+    routing::{get, post},
+    Router,
+};
+use tower::ServiceBuilder;
+use tower_http::{
+    cors::{Any, CorsLayer},
+    trace::TraceLayer,
+};
+
+pub mod attestation;
+pub mod cli;
+pub mod config;
+pub mod extraction;
+pub mod handlers;
+pub mod middleware;
+pub mod models;
+pub mod scoring;
+pub mod simulation;
+pub mod state;
+
+use handlers::{challenge_handler, config_handler, health_handler, score_handler, score_raw_handler, shadow_handler};
+use state::AppState;
+
+/// Set up our API routes and middleware
+pub fn create_app(state: AppState) -> Router {
+    let cors = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers(Any)
+        .allow_origin(Any);
+
+    let rate_limit_layer = middleware::rate_limit::create_service_builder(state.config.rate_limit.clone());
+
+    Router::new()
+        .route("/healthz", get(health_handler))
+        .route("/config", get(config_handler))
+        .route("/shadow", get(shadow_handler))
+        .route("/challenge", get(challenge_handler))
+        .route("/score", post(score_handler))
+        .route("/score/raw", post(score_raw_handler))
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors)
+                .layer(rate_limit_layer)
+        )
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_health_endpoint() {
+        let config = AppConfig::default();
+        let app = Router::new()
+            .route("/healthz", get(health_handler))
+            .with_state(config);
+
+        let request = Request::builder()
+            .uri("/healthz")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_config_endpoint() {
+        let state = AppState::new(AppConfig::default()).unwrap();
+        let app = Router::new()
+            .route("/config", get(config_handler))
+            .route("/shadow", get(shadow_handler))
+            .with_state(state);
+
+        let request = Request::builder()
+            .uri("/config")
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
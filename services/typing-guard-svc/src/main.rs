@@ -1,52 +1,23 @@
-use axum::{
-    extract::State,
-    http::Method,
-    response::Json,
-    routing::{get, post},
-    Router,
-};
 use std::net::SocketAddr;
-use tower::ServiceBuilder;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
-use tracing::{info, Level};
+use tracing::{debug, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-mod config;
-mod handlers;
-mod middleware;
-mod models;
-
-/// Set up our API routes and middleware
-pub fn create_app(config: AppConfig) -> Router {
-    let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST])
-        .allow_headers(Any)
-        .allow_origin(Any);
-
-    let rate_limit_layer = middleware::rate_limit::create_service_builder(config.rate_limit.clone());
-
-    Router::new()
-        .route("/healthz", get(health_handler))
-        .route("/config", get(config_handler))
-        .route("/score", post(score_handler))
-        .layer(
-            ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(cors)
-                .layer(rate_limit_layer)
-        )
-        .with_state(config)
-}
-
-use config::AppConfig;
-use handlers::{config_handler, health_handler, score_handler};
-use middleware::rate_limit::create_service_builder;
+use typing_guard_svc::{cli, config::AppConfig, create_app, state::AppState};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Offline subcommands run before logging so their stdout stays clean
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some((command, rest)) = args.split_first() {
+        if command != "serve" {
+            if let Err(e) = cli::run(command, rest) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+
     // Set up logging
     tracing_subscriber::registry()
         .with(
@@ -61,10 +32,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     config.validate()?;
 
     info!("Starting Typing Guard Service v{}", env!("CARGO_PKG_VERSION"));
-    info!("Configuration: {:?}", config);
+    debug!("Configuration: {:?}", config);
 
-    // Build the app
-    let app = create_app(config);
+    // Build the scorer and the app
+    let state = AppState::new(config)?;
+    info!("Scoring engine: {}", state.pipeline.default_profile().scorer.name());
+    let app = create_app(state);
 
     // Start the server
     let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
@@ -75,47 +48,3 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-    };
-    use tower::ServiceExt;
-
-    #[tokio::test]
-    async fn test_health_endpoint() {
-        let config = AppConfig::default();
-        let app = Router::new()
-            .route("/healthz", get(health_handler))
-            .with_state(config);
-
-        let request = Request::builder()
-            .uri("/healthz")
-            .method("GET")
-            .body(Body::empty())
-            .unwrap();
-
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
-    }
-
-    #[tokio::test]
-    async fn test_config_endpoint() {
-        let config = AppConfig::default();
-        let app = Router::new()
-            .route("/config", get(config_handler))
-            .with_state(config);
-
-        let request = Request::builder()
-            .uri("/config")
-            .method("GET")
-            .body(Body::empty())
-            .unwrap();
-
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
-    }
-}
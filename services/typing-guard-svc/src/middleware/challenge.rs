@@ -0,0 +1,173 @@
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::{
+    config::SecurityConfig,
+    middleware::freshness::{InsertError, SeenKeys},
+    models::response::ChallengeResponse,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Random bytes in a challenge nonce
+const NONCE_BYTES: usize = 16;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ChallengeError {
+    #[error("Payload must carry a challenge from GET /challenge")]
+    Missing,
+
+    #[error("Challenge was not issued by this server")]
+    Invalid,
+
+    #[error("Challenge expired {0} ms ago")]
+    Expired(i64),
+
+    #[error("Challenge was already used")]
+    Used,
+
+    #[error("Too many unexpired challenges redeemed to remember another")]
+    Full,
+}
+
+/// Issues server-signed nonces and accepts each one once, until it expires
+///
+/// A challenge is `<nonce>.<expires_at>.<signature>`, the signature an HMAC-SHA256 of the rest,
+/// so any instance sharing `challenge_key` can check it without a lookup.
+pub struct ChallengeIssuer {
+    key: Vec<u8>,
+    ttl_ms: i64,
+    required: bool,
+    used: SeenKeys,
+}
+
+impl ChallengeIssuer {
+    pub fn new(config: &SecurityConfig) -> Self {
+        // Without a configured key, challenges are only good on this instance until it restarts
+        let key = match &config.challenge_key {
+            Some(key) => key.as_bytes().to_vec(),
+            None => rand::thread_rng().gen::<[u8; 32]>().to_vec(),
+        };
+        Self {
+            key,
+            ttl_ms: config.challenge_ttl_secs as i64 * 1000,
+            required: config.require_challenge,
+            used: SeenKeys::new(config.duplicate_cache_size, config.fail_open_when_full),
+        }
+    }
+
+    /// A fresh challenge and its expiry
+    pub fn issue(&self) -> ChallengeResponse {
+        self.issue_at(chrono::Utc::now().timestamp_millis())
+    }
+
+    fn issue_at(&self, now: i64) -> ChallengeResponse {
+        let nonce = hex::encode(rand::thread_rng().gen::<[u8; NONCE_BYTES]>());
+        let expires_at = now + self.ttl_ms;
+        let body = format!("{}.{}", nonce, expires_at);
+        let signature = hex::encode(self.mac(&body).finalize().into_bytes());
+        ChallengeResponse {
+            challenge: format!("{}.{}", body, signature),
+            expires_at,
+        }
+    }
+
+    /// Accept a payload's challenge if it is genuine, unexpired and unused; none passes unless required
+    pub fn redeem(&self, challenge: Option<&str>) -> Result<(), ChallengeError> {
+        self.redeem_at(chrono::Utc::now().timestamp_millis(), challenge)
+    }
+
+    fn redeem_at(&self, now: i64, challenge: Option<&str>) -> Result<(), ChallengeError> {
+        let Some(challenge) = challenge else {
+            return if self.required { Err(ChallengeError::Missing) } else { Ok(()) };
+        };
+
+        let (body, signature) = challenge.rsplit_once('.').ok_or(ChallengeError::Invalid)?;
+        let signature = hex::decode(signature).map_err(|_| ChallengeError::Invalid)?;
+        self.mac(body)
+            .verify_slice(&signature)
+            .map_err(|_| ChallengeError::Invalid)?;
+
+        let expires_at: i64 = body
+            .split_once('.')
+            .and_then(|(_, expires_at)| expires_at.parse().ok())
+            .ok_or(ChallengeError::Invalid)?;
+        if now > expires_at {
+            return Err(ChallengeError::Expired(now - expires_at));
+        }
+
+        // An expired challenge is rejected above, so it need not be remembered past its expiry
+        match self.used.insert(body.to_string(), expires_at, now) {
+            Ok(()) => Ok(()),
+            Err(InsertError::Present) => Err(ChallengeError::Used),
+            Err(InsertError::Full) => Err(ChallengeError::Full),
+        }
+    }
+
+    fn mac(&self, body: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC can take key of any size");
+        mac.update(body.as_bytes());
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_736_345_678_123;
+
+    fn issuer(key: &str) -> ChallengeIssuer {
+        ChallengeIssuer::new(&SecurityConfig {
+            challenge_key: Some(key.to_string()),
+            require_challenge: true,
+            ..SecurityConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_challenge_accepted_once() {
+        let issuer = issuer("challenge-key");
+        let issued = issuer.issue_at(NOW);
+        assert_eq!(issued.expires_at, NOW + 120_000);
+
+        assert_eq!(issuer.redeem_at(NOW + 1000, Some(&issued.challenge)), Ok(()));
+        assert_eq!(issuer.redeem_at(NOW + 2000, Some(&issued.challenge)), Err(ChallengeError::Used));
+
+        // Another instance sharing the key accepts it, its used set is its own
+        let sibling = self::issuer("challenge-key");
+        assert_eq!(sibling.redeem_at(NOW, Some(&issued.challenge)), Ok(()));
+    }
+
+    #[test]
+    fn test_challenge_rejected() {
+        let issuer = issuer("challenge-key");
+        let issued = issuer.issue_at(NOW);
+
+        assert_eq!(issuer.redeem_at(NOW, None), Err(ChallengeError::Missing));
+        assert_eq!(issuer.redeem_at(NOW + 120_001, Some(&issued.challenge)), Err(ChallengeError::Expired(1)));
+        assert_eq!(issuer.redeem_at(NOW, Some("not-a-challenge")), Err(ChallengeError::Invalid));
+
+        // Pushing the expiry back breaks the signature
+        let (nonce, rest) = issued.challenge.split_once('.').unwrap();
+        let signature = rest.split_once('.').unwrap().1;
+        let extended = format!("{}.{}.{}", nonce, NOW + 3_600_000, signature);
+        assert_eq!(issuer.redeem_at(NOW, Some(&extended)), Err(ChallengeError::Invalid));
+
+        let other = self::issuer("other-key");
+        assert_eq!(other.redeem_at(NOW, Some(&issued.challenge)), Err(ChallengeError::Invalid));
+    }
+
+    #[test]
+    fn test_challenge_optional_unless_required() {
+        let issuer = ChallengeIssuer::new(&SecurityConfig::default());
+        assert_eq!(issuer.redeem_at(NOW, None), Ok(()));
+
+        // One that is sent is still checked
+        let issued = issuer.issue_at(NOW);
+        assert_eq!(issuer.redeem_at(NOW, Some(&issued.challenge)), Ok(()));
+        assert_eq!(issuer.redeem_at(NOW, Some(&issued.challenge)), Err(ChallengeError::Used));
+    }
+}
@@ -0,0 +1,221 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::Mutex,
+};
+
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::SecurityConfig;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FreshnessError {
+    #[error("Timestamp is {0} ms older than the allowed clock skew")]
+    Stale(i64),
+
+    #[error("Timestamp is {0} ms further ahead than the allowed clock skew")]
+    InFuture(i64),
+
+    #[error("Request was already received")]
+    Duplicate,
+
+    #[error("Too many fresh requests to remember another")]
+    Full,
+}
+
+/// Why a key was not remembered
+#[derive(Debug, PartialEq, Eq)]
+pub enum InsertError {
+    /// The key is already remembered
+    Present,
+    /// Every slot holds a key that hasn't expired
+    Full,
+}
+
+#[derive(Default)]
+struct Seen {
+    /// Key and the server time, in ms, after which it can be forgotten
+    expiries: HashMap<String, i64>,
+    /// Keys by expiry, the soonest first
+    order: BinaryHeap<Reverse<(i64, String)>>,
+}
+
+/// Keys remembered until they expire, up to a capacity
+///
+/// A key is never dropped before its expiry, so that a flood of new keys can't push out one
+/// that must still be refused. When full, new keys are refused until room frees up, or with
+/// `fail_open` let through without being remembered.
+pub struct SeenKeys {
+    capacity: usize,
+    fail_open: bool,
+    seen: Mutex<Seen>,
+}
+
+impl SeenKeys {
+    pub fn new(capacity: usize, fail_open: bool) -> Self {
+        Self {
+            capacity,
+            fail_open,
+            seen: Mutex::new(Seen::default()),
+        }
+    }
+
+    /// Remember a key until `expires_at`, unless it is already remembered or there is no room
+    pub fn insert(&self, key: String, expires_at: i64, now: i64) -> Result<(), InsertError> {
+        let mut seen = self.seen.lock().unwrap();
+        while seen.order.peek().is_some_and(|Reverse((expiry, _))| *expiry < now) {
+            let Reverse((_, oldest)) = seen.order.pop().unwrap();
+            seen.expiries.remove(&oldest);
+        }
+
+        if seen.expiries.contains_key(&key) {
+            return Err(InsertError::Present);
+        }
+        if seen.expiries.len() >= self.capacity {
+            if self.fail_open {
+                warn!("Duplicate cache full, letting a key through unremembered");
+                return Ok(());
+            }
+            return Err(InsertError::Full);
+        }
+        seen.expiries.insert(key.clone(), expires_at);
+        seen.order.push(Reverse((expires_at, key)));
+        Ok(())
+    }
+}
+
+/// Rejects payloads whose `ts` is off the server clock, and repeats of fresh ones
+pub struct FreshnessGuard {
+    max_skew_ms: i64,
+    reject_duplicates: bool,
+    seen: SeenKeys,
+}
+
+impl FreshnessGuard {
+    pub fn new(config: &SecurityConfig) -> Self {
+        Self {
+            max_skew_ms: config.max_clock_skew_secs as i64 * 1000,
+            reject_duplicates: config.reject_duplicates,
+            seen: SeenKeys::new(config.duplicate_cache_size, config.fail_open_when_full),
+        }
+    }
+
+    /// Check a payload's timestamp against the server clock
+    pub fn check(&self, ts: i64) -> Result<(), FreshnessError> {
+        self.check_at(chrono::Utc::now().timestamp_millis(), ts)
+    }
+
+    fn check_at(&self, now: i64, ts: i64) -> Result<(), FreshnessError> {
+        if self.max_skew_ms == 0 {
+            return Ok(());
+        }
+        if ts < now - self.max_skew_ms {
+            return Err(FreshnessError::Stale(now - self.max_skew_ms - ts));
+        }
+        if ts > now + self.max_skew_ms {
+            return Err(FreshnessError::InFuture(ts - now - self.max_skew_ms));
+        }
+        Ok(())
+    }
+
+    /// Remember a checked payload, keyed by its nonce or (`session_id`, `ts`), or reject a repeat
+    ///
+    /// Called once the payload passed every other check, so a rejected one can be sent again.
+    pub fn record(&self, session_id: Uuid, ts: i64, nonce: Option<&str>) -> Result<(), FreshnessError> {
+        self.record_at(chrono::Utc::now().timestamp_millis(), session_id, ts, nonce)
+    }
+
+    fn record_at(&self, now: i64, session_id: Uuid, ts: i64, nonce: Option<&str>) -> Result<(), FreshnessError> {
+        // Without a skew bound there is no window to remember duplicates for
+        if self.max_skew_ms == 0 || !self.reject_duplicates {
+            return Ok(());
+        }
+
+        let key = match nonce {
+            Some(nonce) => format!("nonce:{}", nonce),
+            None => format!("{}:{}", session_id, ts),
+        };
+        // Past its skew window the payload is rejected as stale, so the key can go
+        match self.seen.insert(key, ts + self.max_skew_ms, now) {
+            Ok(()) => Ok(()),
+            Err(InsertError::Present) => Err(FreshnessError::Duplicate),
+            Err(InsertError::Full) => Err(FreshnessError::Full),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_736_345_678_123;
+
+    fn guard(capacity: usize) -> FreshnessGuard {
+        FreshnessGuard::new(&SecurityConfig {
+            duplicate_cache_size: capacity,
+            ..SecurityConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        let guard = guard(100);
+        assert_eq!(guard.check_at(NOW, NOW - 300_000), Ok(()));
+        assert_eq!(guard.check_at(NOW, NOW + 300_000), Ok(()));
+        assert_eq!(guard.check_at(NOW, NOW - 300_001), Err(FreshnessError::Stale(1)));
+        assert_eq!(guard.check_at(NOW, NOW + 310_000), Err(FreshnessError::InFuture(10_000)));
+    }
+
+    #[test]
+    fn test_duplicates_rejected_while_fresh() {
+        let guard = guard(100);
+        let session = Uuid::new_v4();
+
+        assert_eq!(guard.record_at(NOW, session, NOW, None), Ok(()));
+        assert_eq!(guard.record_at(NOW + 1000, session, NOW, None), Err(FreshnessError::Duplicate));
+        assert_eq!(guard.record_at(NOW + 1000, session, NOW + 1, None), Ok(()));
+        assert_eq!(guard.record_at(NOW, Uuid::new_v4(), NOW, None), Ok(()));
+
+        // A nonce is single-use whatever the session and timestamp
+        assert_eq!(guard.record_at(NOW, session, NOW + 2, Some("abc")), Ok(()));
+        assert_eq!(guard.record_at(NOW, Uuid::new_v4(), NOW + 3, Some("abc")), Err(FreshnessError::Duplicate));
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let guard = guard(2);
+        let captured = Uuid::new_v4();
+        guard.record_at(NOW, captured, NOW, None).unwrap();
+        guard.record_at(NOW, Uuid::new_v4(), NOW + 1, None).unwrap();
+
+        // A full cache refuses new keys rather than forgetting fresh ones
+        let session = Uuid::new_v4();
+        assert_eq!(guard.record_at(NOW, session, NOW + 2, None), Err(FreshnessError::Full));
+        assert_eq!(guard.record_at(NOW, captured, NOW, None), Err(FreshnessError::Duplicate));
+
+        // Once the oldest expires, its slot is free again
+        let later = NOW + 300_001;
+        assert_eq!(guard.record_at(later, session, NOW + 2, None), Ok(()));
+        assert_eq!(guard.record_at(later, Uuid::new_v4(), NOW + 3, None), Err(FreshnessError::Full));
+
+        let seen = guard.seen.seen.lock().unwrap();
+        assert_eq!((seen.order.len(), seen.expiries.len()), (2, 2));
+    }
+
+    #[test]
+    fn test_full_cache_can_fail_open() {
+        let guard = FreshnessGuard::new(&SecurityConfig {
+            duplicate_cache_size: 1,
+            fail_open_when_full: true,
+            ..SecurityConfig::default()
+        });
+        let session = Uuid::new_v4();
+        guard.record_at(NOW, Uuid::new_v4(), NOW, None).unwrap();
+
+        // Let through, but not remembered, so a repeat gets through as well
+        assert_eq!(guard.record_at(NOW, session, NOW, None), Ok(()));
+        assert_eq!(guard.record_at(NOW, session, NOW, None), Ok(()));
+    }
+}
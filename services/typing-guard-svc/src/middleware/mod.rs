@@ -1,3 +1,5 @@
+pub mod challenge;
+pub mod freshness;
 pub mod hmac;
 pub mod rate_limit;
 
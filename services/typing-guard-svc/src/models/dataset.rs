@@ -0,0 +1,200 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    features::TypingFeatures,
+    payload::{TypingFeaturePayload, TypingMeta},
+};
+
+#[derive(Error, Debug)]
+pub enum DatasetError {
+    #[error("Failed to read dataset: {0}")]
+    Io(#[from] std::io::Error),
+    
+    #[error("Line {line}: {message}")]
+    Invalid { line: usize, message: String },
+
+    #[error("Unsupported dataset format: {0} (expected .jsonl or .csv)")]
+    UnsupportedFormat(String),
+}
+
+/// Ground-truth label of a dataset record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Label {
+    Human,
+    Bot,
+}
+
+/// A scoring payload with its ground-truth label
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledPayload {
+    /// Whether a human or a bot produced the payload
+    pub label: Label,
+    
+    /// The payload as it would be sent to `/score`
+    #[serde(flatten)]
+    pub payload: TypingFeaturePayload,
+}
+
+impl LabeledPayload {
+    pub fn is_human(&self) -> bool {
+        self.label == Label::Human
+    }
+}
+
+/// One row of a CSV dataset: the label, the features and optional metadata columns
+#[derive(Debug, Deserialize)]
+struct CsvRecord {
+    label: Label,
+    #[serde(default)]
+    session_id: Option<Uuid>,
+    #[serde(default)]
+    ts: Option<i64>,
+    events: u32,
+    iki_mean: f64,
+    iki_std: f64,
+    iki_iqr: f64,
+    burstiness: f64,
+    entropy: f64,
+    backspace_per_100: f64,
+    paste_events: u32,
+    jitter_mad: f64,
+    outlier_ratio: f64,
+    #[serde(default)]
+    window_ms: Option<u32>,
+    #[serde(default)]
+    locale: Option<String>,
+    #[serde(default)]
+    platform: Option<String>,
+    #[serde(default)]
+    app_ver: Option<String>,
+}
+
+impl CsvRecord {
+    fn into_labeled(self) -> LabeledPayload {
+        let features = TypingFeatures {
+            events: self.events,
+            iki_mean: self.iki_mean,
+            iki_std: self.iki_std,
+            iki_iqr: self.iki_iqr,
+            burstiness: self.burstiness,
+            entropy: self.entropy,
+            backspace_per_100: self.backspace_per_100,
+            paste_events: self.paste_events,
+            jitter_mad: self.jitter_mad,
+            outlier_ratio: self.outlier_ratio,
+        };
+        // Metadata is only attached when the row names a platform
+        let meta = self.platform.map(|platform| TypingMeta {
+            window_ms: self.window_ms.unwrap_or(0),
+            locale: self.locale.unwrap_or_default(),
+            platform,
+            app_ver: self.app_ver.unwrap_or_default(),
+        });
+
+        let mut payload = TypingFeaturePayload::new(features, meta);
+        if let Some(session_id) = self.session_id {
+            payload.session_id = session_id;
+        }
+        if let Some(ts) = self.ts {
+            payload.ts = ts;
+        }
+        LabeledPayload {
+            label: self.label,
+            payload,
+        }
+    }
+}
+
+/// Load labeled payloads from a `.jsonl` or `.csv` file, by extension
+pub fn load(path: &str) -> Result<Vec<LabeledPayload>, DatasetError> {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "jsonl" | "ndjson" => load_jsonl(path),
+        "csv" => load_csv(path),
+        _ => Err(DatasetError::UnsupportedFormat(path.to_string())),
+    }
+}
+
+/// Load labeled payloads from a JSONL file, one record per line
+pub fn load_jsonl(path: &str) -> Result<Vec<LabeledPayload>, DatasetError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let invalid = |message: String| DatasetError::Invalid {
+            line: index + 1,
+            message,
+        };
+        let record: LabeledPayload = serde_json::from_str(&line).map_err(|e| invalid(e.to_string()))?;
+        record.payload.validate().map_err(invalid)?;
+        records.push(record);
+    }
+    
+    Ok(records)
+}
+
+/// Load labeled payloads from a CSV file with a header row
+///
+/// Columns are `label`, the ten feature fields and optionally `session_id`, `ts`,
+/// `window_ms`, `locale`, `platform` and `app_ver`, in any order.
+pub fn load_csv(path: &str) -> Result<Vec<LabeledPayload>, DatasetError> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(path).map_err(std::io::Error::from)?;
+    let mut records = Vec::new();
+
+    for (index, row) in reader.deserialize::<CsvRecord>().enumerate() {
+        // Line 1 is the header
+        let invalid = |message: String| DatasetError::Invalid {
+            line: index + 2,
+            message,
+        };
+        let record = row.map_err(|e| invalid(e.to_string()))?.into_labeled();
+        record.payload.validate().map_err(invalid)?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_csv() {
+        let path = std::env::temp_dir().join(format!("dataset-{}.csv", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "label,events,iki_mean,iki_std,iki_iqr,burstiness,entropy,backspace_per_100,paste_events,jitter_mad,outlier_ratio,platform\n\
+             human,40,150,50,60,-0.5,2.8,5,0,25,0.05,web\n\
+             bot,40,20,1,1,-0.9,0.5,0,0,0.5,0,\n",
+        )
+        .unwrap();
+
+        let records = load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_human());
+        assert_eq!(records[0].payload.meta.as_ref().unwrap().platform, "web");
+        assert_eq!(records[1].label, Label::Bot);
+        assert!(records[1].payload.meta.is_none());
+        assert_eq!(records[1].payload.features.iki_mean, 20.0);
+    }
+}
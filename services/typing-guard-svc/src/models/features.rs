@@ -35,39 +35,79 @@ pub struct TypingFeatures {
 }
 
 impl TypingFeatures {
+    /// Field names, in `values` order, as used by model files
+    pub const FIELD_NAMES: [&'static str; 10] = [
+        "events",
+        "iki_mean",
+        "iki_std",
+        "iki_iqr",
+        "burstiness",
+        "entropy",
+        "backspace_per_100",
+        "paste_events",
+        "jitter_mad",
+        "outlier_ratio",
+    ];
+
+    /// Position of a field in `values`, if the name is known
+    pub fn field_index(name: &str) -> Option<usize> {
+        Self::FIELD_NAMES.iter().position(|field| *field == name)
+    }
+
+    /// All fields as numbers, in `FIELD_NAMES` order
+    pub fn values(&self) -> [f64; 10] {
+        [
+            self.events as f64,
+            self.iki_mean,
+            self.iki_std,
+            self.iki_iqr,
+            self.burstiness,
+            self.entropy,
+            self.backspace_per_100,
+            self.paste_events as f64,
+            self.jitter_mad,
+            self.outlier_ratio,
+        ]
+    }
+
     /// Calculate human-likeness score based on features
-    pub fn calculate_score(&self, weights: &FeatureWeights) -> f64 {
-        let speed_score = self.normalize_speed();
-        let variability_score = self.normalize_variability();
-        let entropy_score = self.normalize_entropy();
-        let backspace_score = self.normalize_backspace();
-        let jitter_score = self.normalize_jitter();
-
-        let score = (speed_score * weights.speed) +
-                   (variability_score * weights.variability) +
-                   (entropy_score * weights.entropy) +
-                   (backspace_score * weights.backspace) +
-                   (jitter_score * weights.jitter);
+    pub fn calculate_score(&self, weights: &FeatureWeights, curves: &NormalizationCurves) -> f64 {
+        let score: f64 = self
+            .breakdown(weights, curves)
+            .iter()
+            .map(|feature| feature.contribution)
+            .sum();
 
         score.clamp(0.0, 1.0)
     }
 
-    /// Normalize speed (IKI mean) to 0-1 score
-    fn normalize_speed(&self) -> f64 {
-        // Human typing typically 100-300ms, optimal around 150ms
-        if self.iki_mean < 50.0 {
-            return 0.0; // Too fast (bot-like)
-        }
-        if self.iki_mean > 1000.0 {
-            return 0.0; // Too slow (suspicious)
-        }
-        if self.iki_mean >= 100.0 && self.iki_mean <= 300.0 {
-            return 1.0; // Optimal range
-        }
-        if self.iki_mean < 100.0 {
-            return self.iki_mean / 100.0; // Linear scaling for fast typing
-        }
-        (1.0 - (self.iki_mean - 300.0) / 700.0).max(0.0) // Linear scaling for slow typing
+    /// Normalized sub-score of every weighted feature, in `FeatureWeights::entries` order
+    pub fn sub_scores(&self, curves: &NormalizationCurves) -> [(&'static str, f64); 8] {
+        [
+            ("speed", curves.speed.eval(self.iki_mean)),
+            ("variability", self.normalize_variability()),
+            ("entropy", curves.entropy.eval(self.entropy)),
+            ("backspace", curves.backspace.eval(self.backspace_per_100)),
+            ("jitter", curves.jitter.eval(self.jitter_mad)),
+            // IQR relative to the mean, so the curve works across typing speeds
+            ("iqr", if self.iki_mean > 0.0 { curves.iqr.eval(self.iki_iqr / self.iki_mean) } else { 0.0 }),
+            ("outliers", curves.outliers.eval(self.outlier_ratio)),
+            ("paste", self.normalize_paste()),
+        ]
+    }
+
+    /// Per-feature sub-scores and their weighted contributions
+    pub fn breakdown(&self, weights: &FeatureWeights, curves: &NormalizationCurves) -> Vec<FeatureScore> {
+        self.sub_scores(curves)
+            .iter()
+            .zip(weights.entries())
+            .map(|(&(feature, score), (_, weight))| FeatureScore {
+                feature: feature.to_string(),
+                score,
+                weight,
+                contribution: score * weight,
+            })
+            .collect()
     }
 
     /// Normalize variability to 0-1 score
@@ -78,52 +118,224 @@ impl TypingFeatures {
         (std_score + burst_score) / 2.0
     }
 
-    /// Normalize entropy to 0-1 score
-    fn normalize_entropy(&self) -> f64 {
-        // Optimal entropy around 2.5-3.5 for human typing
-        if self.entropy < 1.0 {
-            return 0.0; // Too low (bot-like)
+    /// Normalize paste events (relative to all events) to 0-1 score
+    fn normalize_paste(&self) -> f64 {
+        // Typed text has no pastes, anything pasted didn't come from the keyboard
+        if self.paste_events == 0 {
+            return 1.0;
         }
-        if self.entropy > 4.0 {
-            return 0.0; // Too high (random)
-        }
-        if self.entropy >= 2.0 && self.entropy <= 3.5 {
-            return 1.0; // Optimal range
-        }
-        self.entropy / 3.5 // Linear scaling
+        let ratio = self.paste_events as f64 / self.events as f64;
+        (1.0 - ratio * 5.0).max(0.0) // 20% or more pastes scores zero
     }
+}
+
+/// Normalization curve mapping a raw feature value to a 0-1 sub-score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Curve {
+    /// Floor below `lower_cutoff`, ramp up to 1.0 on the plateau, ramp down to a floor past `upper_cutoff`
+    Trapezoid {
+        lower_cutoff: f64,
+        plateau_start: f64,
+        plateau_end: f64,
+        upper_cutoff: f64,
+        #[serde(default)]
+        lower_floor: f64,
+        #[serde(default)]
+        upper_floor: f64,
+    },
+    
+    /// Linear interpolation between `[value, score]` breakpoints, flat outside them.
+    /// Repeating a value makes a step, and the value itself scores the later point.
+    Piecewise { points: Vec<[f64; 2]> },
+}
 
-    /// Normalize backspace rate to 0-1 score
-    fn normalize_backspace(&self) -> f64 {
-        // Humans make some mistakes, but not too many
-        if self.backspace_per_100 < 1.0 {
-            return 0.5; // Very few mistakes (suspicious)
+impl Curve {
+    pub const fn trapezoid(
+        lower_cutoff: f64,
+        plateau: (f64, f64),
+        upper_cutoff: f64,
+        floors: (f64, f64),
+    ) -> Self {
+        Self::Trapezoid {
+            lower_cutoff,
+            plateau_start: plateau.0,
+            plateau_end: plateau.1,
+            upper_cutoff,
+            lower_floor: floors.0,
+            upper_floor: floors.1,
         }
-        if self.backspace_per_100 > 20.0 {
-            return 0.0; // Too many mistakes
+    }
+
+    pub fn piecewise(points: &[[f64; 2]]) -> Self {
+        Self::Piecewise { points: points.to_vec() }
+    }
+
+    /// Score a raw feature value; NaN scores 0.0
+    pub fn eval(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            return 0.0;
         }
-        if self.backspace_per_100 >= 2.0 && self.backspace_per_100 <= 10.0 {
-            return 1.0; // Optimal range
+        match *self {
+            Self::Trapezoid {
+                lower_cutoff,
+                plateau_start,
+                plateau_end,
+                upper_cutoff,
+                lower_floor,
+                upper_floor,
+            } => {
+                if value < lower_cutoff {
+                    lower_floor
+                } else if value < plateau_start {
+                    lower_floor + (1.0 - lower_floor) * (value - lower_cutoff) / (plateau_start - lower_cutoff)
+                } else if value <= plateau_end {
+                    1.0
+                } else if value <= upper_cutoff {
+                    1.0 - (1.0 - upper_floor) * (value - plateau_end) / (upper_cutoff - plateau_end)
+                } else {
+                    upper_floor
+                }
+            }
+            Self::Piecewise { ref points } => {
+                let (first, last) = (points[0], points[points.len() - 1]);
+                if value < first[0] {
+                    return first[1];
+                }
+                if value >= last[0] {
+                    return last[1];
+                }
+                // Last segment starting at or before the value, so steps take their upper side
+                let i = points.iter().rposition(|point| point[0] <= value).unwrap_or(0);
+                let (start, end) = (points[i], points[i + 1]);
+                start[1] + (end[1] - start[1]) * (value - start[0]) / (end[0] - start[0])
+            }
         }
-        1.0 - (self.backspace_per_100 - 10.0) / 10.0 // Linear scaling
     }
 
-    /// Normalize jitter to 0-1 score
-    fn normalize_jitter(&self) -> f64 {
-        // Some jitter is human-like, too much or too little is suspicious
-        if self.jitter_mad < 5.0 {
-            return 0.3; // Too consistent
+    /// Check that the curve is well-formed and stays within 0-1
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        let in_unit = |score: f64| (0.0..=1.0).contains(&score);
+        match *self {
+            Self::Trapezoid {
+                lower_cutoff,
+                plateau_start,
+                plateau_end,
+                upper_cutoff,
+                lower_floor,
+                upper_floor,
+            } => {
+                let cutoffs = [lower_cutoff, plateau_start, plateau_end, upper_cutoff];
+                if cutoffs.iter().any(|cutoff| !cutoff.is_finite()) {
+                    return Err(format!("Curve '{}' cutoffs must be finite", name));
+                }
+                if !(lower_cutoff <= plateau_start && plateau_start <= plateau_end && plateau_end <= upper_cutoff) {
+                    return Err(format!(
+                        "Curve '{}' must satisfy lower_cutoff <= plateau_start <= plateau_end <= upper_cutoff",
+                        name
+                    ));
+                }
+                if !in_unit(lower_floor) || !in_unit(upper_floor) {
+                    return Err(format!("Curve '{}' floors must be between 0.0 and 1.0", name));
+                }
+            }
+            Self::Piecewise { ref points } => {
+                if points.len() < 2 {
+                    return Err(format!("Curve '{}' needs at least two points", name));
+                }
+                if points.iter().any(|point| !point[0].is_finite() || !in_unit(point[1])) {
+                    return Err(format!("Curve '{}' points must be finite with scores between 0.0 and 1.0", name));
+                }
+                if points.windows(2).any(|pair| pair[1][0] < pair[0][0]) {
+                    return Err(format!("Curve '{}' points must be sorted by value", name));
+                }
+            }
         }
-        if self.jitter_mad > 100.0 {
-            return 0.0; // Too erratic
-        }
-        if self.jitter_mad >= 10.0 && self.jitter_mad <= 50.0 {
-            return 1.0; // Optimal range
+        Ok(())
+    }
+}
+
+/// Normalization curve of every curve-based feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizationCurves {
+    /// IKI mean in milliseconds
+    pub speed: Curve,
+    
+    /// Interval entropy in bits
+    pub entropy: Curve,
+    
+    /// Backspaces per 100 keystrokes
+    pub backspace: Curve,
+    
+    /// Jitter MAD in milliseconds
+    pub jitter: Curve,
+    
+    /// IKI IQR divided by IKI mean
+    pub iqr: Curve,
+    
+    /// Share of outlier intervals
+    pub outliers: Curve,
+}
+
+impl Default for NormalizationCurves {
+    fn default() -> Self {
+        Self {
+            // The original hand-written curves, sub-scores above 1.0 clamped. Steps score their
+            // later point, so 4 bits and 100ms now fall outside where they used to score 1.0.
+            // Human typing typically 100-300ms, proportional below 100ms
+            speed: Curve::piecewise(&[[50.0, 0.0], [50.0, 0.5], [100.0, 1.0], [300.0, 1.0], [1000.0, 0.0]]),
+            // Below 1 bit is bot-like, above 4 is random; entropy / 3.5 below 2 bits
+            entropy: Curve::piecewise(&[
+                [1.0, 0.0],
+                [1.0, 1.0 / 3.5],
+                [2.0, 2.0 / 3.5],
+                [2.0, 1.0],
+                [4.0, 1.0],
+                [4.0, 0.0],
+            ]),
+            // Humans make some mistakes, but not too many
+            backspace: Curve::piecewise(&[[1.0, 0.5], [1.0, 1.0], [10.0, 1.0], [20.0, 0.0]]),
+            // Some jitter is human-like; jitter / 50 from 5 to 10ms
+            jitter: Curve::piecewise(&[[5.0, 0.3], [5.0, 0.1], [10.0, 0.2], [10.0, 1.0], [100.0, 1.0], [100.0, 0.0]]),
+            // Humans spread their middle half over a good chunk of their typing speed
+            iqr: Curve::trapezoid(0.05, (0.2, 0.9), 1.5, (0.0, 0.0)),
+            // Humans pause now and then (0.5-5% of intervals past 3σ). Chebyshev caps
+            // the share at 1/9, so anything near it is fabricated and scores zero.
+            outliers: Curve::trapezoid(0.0, (0.005, 0.05), 0.1, (0.3, 0.0)),
         }
-        self.jitter_mad / 50.0 // Linear scaling
     }
 }
 
+impl NormalizationCurves {
+    /// Validate every curve
+    pub fn validate(&self) -> Result<(), String> {
+        self.speed.validate("speed")?;
+        self.entropy.validate("entropy")?;
+        self.backspace.validate("backspace")?;
+        self.jitter.validate("jitter")?;
+        self.iqr.validate("iqr")?;
+        self.outliers.validate("outliers")?;
+        Ok(())
+    }
+}
+
+/// Per-feature result of the weighted heuristic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureScore {
+    /// Feature name, matching the `FeatureWeights` field
+    pub feature: String,
+    
+    /// Normalized sub-score
+    pub score: f64,
+    
+    /// Weight applied to the sub-score
+    pub weight: f64,
+    
+    /// Weighted contribution to the final score
+    pub contribution: f64,
+}
+
 /// Feature weights for scoring algorithm
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureWeights {
@@ -132,27 +344,246 @@ pub struct FeatureWeights {
     pub entropy: f64,
     pub backspace: f64,
     pub jitter: f64,
+    #[serde(default)]
+    pub iqr: f64,
+    #[serde(default)]
+    pub outliers: f64,
+    #[serde(default)]
+    pub paste: f64,
 }
 
 impl Default for FeatureWeights {
     fn default() -> Self {
         Self {
-            speed: 0.3,
-            variability: 0.25,
-            entropy: 0.2,
-            backspace: 0.15,
+            speed: 0.25,
+            variability: 0.2,
+            entropy: 0.15,
+            backspace: 0.1,
             jitter: 0.1,
+            iqr: 0.05,
+            outliers: 0.05,
+            paste: 0.1,
         }
     }
 }
 
 impl FeatureWeights {
-    /// Validate that weights sum to approximately 1.0
+    /// All weights as (name, value) pairs
+    pub fn entries(&self) -> [(&'static str, f64); 8] {
+        [
+            ("speed", self.speed),
+            ("variability", self.variability),
+            ("entropy", self.entropy),
+            ("backspace", self.backspace),
+            ("jitter", self.jitter),
+            ("iqr", self.iqr),
+            ("outliers", self.outliers),
+            ("paste", self.paste),
+        ]
+    }
+
+    /// Validate that weights are non-negative and sum to approximately 1.0
     pub fn validate(&self) -> Result<(), String> {
-        let sum = self.speed + self.variability + self.entropy + self.backspace + self.jitter;
+        for (name, weight) in self.entries() {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("Feature weight '{}' must be a non-negative number, got {}", name, weight));
+            }
+        }
+
+        let sum: f64 = self.entries().iter().map(|(_, weight)| weight).sum();
         if (sum - 1.0).abs() > 0.01 {
             return Err(format!("Feature weights must sum to approximately 1.0, got {}", sum));
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed_features() -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean: 150.0,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: 0.2,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.03,
+        }
+    }
+
+    #[test]
+    fn test_paste_heavy_scores_lower() {
+        let weights = FeatureWeights::default();
+        let typed = typed_features();
+        let pasted = TypingFeatures {
+            paste_events: 10,
+            ..typed_features()
+        };
+
+        let curves = NormalizationCurves::default();
+        assert!(pasted.calculate_score(&weights, &curves) < typed.calculate_score(&weights, &curves));
+        assert_eq!(pasted.normalize_paste(), 0.0);
+    }
+
+    #[test]
+    fn test_outlier_and_iqr_ranges() {
+        let curves = NormalizationCurves::default();
+        let sub_score = |features: &TypingFeatures, name: &str| {
+            features.sub_scores(&curves).iter().find(|(feature, _)| *feature == name).unwrap().1
+        };
+        let human = typed_features();
+        assert_eq!(sub_score(&human, "outliers"), 1.0);
+        assert_eq!(sub_score(&human, "iqr"), 1.0);
+
+        let metronome = TypingFeatures {
+            iki_iqr: 1.0,
+            outlier_ratio: 0.0,
+            ..typed_features()
+        };
+        assert_eq!(sub_score(&metronome, "iqr"), 0.0);
+        assert_eq!(sub_score(&metronome, "outliers"), 0.3);
+
+        // More than 1/9 of intervals past 3σ is impossible, and close to it gets nothing
+        let forged = TypingFeatures {
+            outlier_ratio: 0.1,
+            ..typed_features()
+        };
+        assert_eq!(sub_score(&forged, "outliers"), 0.0);
+    }
+
+    #[test]
+    fn test_paste_only_window_scores_finite() {
+        let pasted = TypingFeatures {
+            events: 20,
+            iki_mean: 0.0,
+            iki_std: 0.0,
+            iki_iqr: 0.0,
+            burstiness: 0.0,
+            entropy: 0.0,
+            backspace_per_100: 0.0,
+            paste_events: 20,
+            jitter_mad: 0.0,
+            outlier_ratio: 0.0,
+        };
+        let curves = NormalizationCurves {
+            iqr: Curve::piecewise(&[[0.05, 0.0], [0.2, 1.0], [0.9, 1.0], [1.5, 0.0]]),
+            ..NormalizationCurves::default()
+        };
+
+        let score = pasted.calculate_score(&FeatureWeights::default(), &curves);
+        assert!(score.is_finite() && score < 0.5, "{}", score);
+        assert_eq!(curves.iqr.eval(f64::NAN), 0.0);
+    }
+
+    #[test]
+    fn test_breakdown_sums_to_score() {
+        let weights = FeatureWeights::default();
+        let curves = NormalizationCurves::default();
+        let features = typed_features();
+        let breakdown = features.breakdown(&weights, &curves);
+
+        assert_eq!(breakdown.len(), 8);
+        let total: f64 = breakdown.iter().map(|f| f.contribution).sum();
+        assert!((total.clamp(0.0, 1.0) - features.calculate_score(&weights, &curves)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trapezoid_curve() {
+        let curve = Curve::trapezoid(5.0, (10.0, 50.0), 100.0, (0.3, 0.0));
+        assert_eq!(curve.eval(1.0), 0.3);
+        assert!((curve.eval(7.5) - 0.65).abs() < 1e-9);
+        assert_eq!(curve.eval(30.0), 1.0);
+        assert!((curve.eval(75.0) - 0.5).abs() < 1e-9);
+        assert_eq!(curve.eval(150.0), 0.0);
+        assert!(curve.validate("jitter").is_ok());
+        assert!(Curve::trapezoid(10.0, (5.0, 50.0), 100.0, (0.0, 0.0)).validate("jitter").is_err());
+        assert!(Curve::trapezoid(5.0, (10.0, 50.0), 100.0, (1.5, 0.0)).validate("jitter").is_err());
+    }
+
+    #[test]
+    fn test_piecewise_curve() {
+        // Hard step at 50ms, then a ramp up to the plateau
+        let curve = Curve::Piecewise {
+            points: vec![[50.0, 0.0], [50.0, 0.5], [100.0, 1.0], [300.0, 1.0], [1000.0, 0.0]],
+        };
+        assert_eq!(curve.eval(20.0), 0.0);
+        assert_eq!(curve.eval(50.0), 0.5);
+        assert!((curve.eval(75.0) - 0.75).abs() < 1e-9);
+        assert_eq!(curve.eval(200.0), 1.0);
+        assert!((curve.eval(650.0) - 0.5).abs() < 1e-9);
+        assert_eq!(curve.eval(5000.0), 0.0);
+        assert!(curve.validate("speed").is_ok());
+
+        let unsorted = Curve::Piecewise {
+            points: vec![[100.0, 1.0], [50.0, 0.0]],
+        };
+        assert!(unsorted.validate("speed").is_err());
+    }
+
+    #[test]
+    fn test_default_curves_match_original_formulas() {
+        // The hand-written normalizers the default curves replaced, clamped to 0-1
+        let speed = |x: f64| match x {
+            x if !(50.0..=1000.0).contains(&x) => 0.0,
+            x if x < 100.0 => x / 100.0,
+            x if x <= 300.0 => 1.0,
+            x => 1.0 - (x - 300.0) / 700.0,
+        };
+        let entropy = |x: f64| match x {
+            x if !(1.0..=4.0).contains(&x) => 0.0,
+            x if (2.0..=3.5).contains(&x) => 1.0,
+            x => (x / 3.5).min(1.0),
+        };
+        let backspace = |x: f64| match x {
+            x if x < 1.0 => 0.5,
+            x if x > 20.0 => 0.0,
+            x => (1.0 - (x - 10.0) / 10.0).min(1.0),
+        };
+        let jitter = |x: f64| match x {
+            x if x < 5.0 => 0.3,
+            x if x > 100.0 => 0.0,
+            x if (10.0..=50.0).contains(&x) => 1.0,
+            x => (x / 50.0).min(1.0),
+        };
+
+        let check = |curve: &Curve, original: &dyn Fn(f64) -> f64, values: &[f64]| {
+            for &value in values {
+                let (got, expected) = (curve.eval(value), original(value));
+                assert!((got - expected).abs() < 1e-9, "{:?} at {}: {} != {}", curve, value, got, expected);
+            }
+        };
+        let curves = NormalizationCurves::default();
+        check(&curves.speed, &speed, &[0.0, 49.9, 50.0, 75.0, 99.9, 100.0, 300.0, 650.0, 1000.0, 1000.1]);
+        check(&curves.entropy, &entropy, &[0.5, 1.0, 1.5, 1.99, 2.0, 3.5, 3.75, 3.99, 4.01]);
+        check(&curves.backspace, &backspace, &[0.0, 0.99, 1.0, 1.5, 2.0, 10.0, 15.0, 20.0, 20.1]);
+        check(&curves.jitter, &jitter, &[0.0, 4.99, 5.0, 7.0, 9.99, 10.0, 50.0, 75.0, 99.99, 100.1]);
+
+        // The upper cutoffs themselves take the step's later point
+        assert_eq!(curves.entropy.eval(4.0), 0.0);
+        assert_eq!(curves.jitter.eval(100.0), 0.0);
+    }
+
+    #[test]
+    fn test_weights_validation() {
+        assert!(FeatureWeights::default().validate().is_ok());
+
+        let negative = FeatureWeights {
+            speed: 0.45,
+            paste: -0.1,
+            ..FeatureWeights::default()
+        };
+        assert!(negative.validate().is_err());
+
+        let short = FeatureWeights {
+            paste: 0.0,
+            ..FeatureWeights::default()
+        };
+        assert!(short.validate().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod dataset;
 pub mod features;
 pub mod payload;
 pub mod response;
@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::models::features::TypingFeatures;
+use crate::{extraction, models::features::TypingFeatures};
+
+/// Upper bound on the events of a raw payload
+pub const MAX_RAW_EVENTS: usize = 2000;
+
+/// Slack on the consistency checks, for rounding and small-sample effects
+const CONSISTENCY_TOLERANCE: f64 = 0.05;
 
 /// Metadata about the typing session
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +39,14 @@ pub struct TypingFeaturePayload {
     
     /// Optional metadata
     pub meta: Option<TypingMeta>,
+    
+    /// Optional single-use client nonce, deduplicated instead of (`session_id`, `ts`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    
+    /// Challenge from `GET /challenge`, accepted once before it expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
 }
 
 impl TypingFeaturePayload {
@@ -43,16 +57,29 @@ impl TypingFeaturePayload {
             ts: chrono::Utc::now().timestamp_millis(),
             features,
             meta,
+            nonce: None,
+            challenge: None,
         }
     }
 
     /// Validate the payload
     pub fn validate(&self) -> Result<(), String> {
+        self.check(false)
+    }
+
+    /// Validate features the server extracted from raw events, where a window of only
+    /// pastes has no interval and so a zero IKI mean
+    pub fn validate_extracted(&self) -> Result<(), String> {
+        self.check(true)
+    }
+
+    fn check(&self, paste_only_allowed: bool) -> Result<(), String> {
         if self.features.events == 0 {
             return Err("No events in features".to_string());
         }
         
-        if self.features.iki_mean <= 0.0 {
+        let paste_only = paste_only_allowed && self.features.iki_mean == 0.0 && self.features.paste_events > 0;
+        if self.features.iki_mean <= 0.0 && !paste_only {
             return Err("Invalid IKI mean".to_string());
         }
         
@@ -82,4 +109,253 @@ impl TypingFeaturePayload {
         
         Ok(())
     }
+
+    /// Names of the cross-feature checks that no real set of intervals could fail, allowing for
+    /// up to `zero_interval_share` of the intervals to be zero-length
+    pub fn inconsistencies(&self, zero_interval_share: f64) -> Vec<&'static str> {
+        let f = &self.features;
+        let mut failed = Vec::new();
+        
+        // Quartiles lie within √3σ of the mean (Cantelli), so IQR ≤ 2√3σ
+        if f.iki_iqr > 2.0 * 3f64.sqrt() * f.iki_std * (1.0 + CONSISTENCY_TOLERANCE) + CONSISTENCY_TOLERANCE {
+            failed.push("iqr_exceeds_std");
+        }
+        
+        let burstiness = if f.iki_std > 0.0 {
+            (f.iki_std - f.iki_mean) / (f.iki_std + f.iki_mean)
+        } else {
+            0.0
+        };
+        if (f.burstiness - burstiness).abs() > CONSISTENCY_TOLERANCE {
+            failed.push("burstiness_mismatch");
+        }
+        
+        // Chebyshev: fewer than 1/9 of the intervals can lie beyond 3σ
+        if f.outlier_ratio > 1.0 / 9.0 {
+            failed.push("outlier_ratio_impossible");
+        }
+        
+        // A 10-bin histogram carries at most log2(10) bits
+        if f.entropy > 10f64.log2() + 1e-9 {
+            failed.push("entropy_impossible");
+        }
+        
+        if f.paste_events > f.events {
+            failed.push("paste_exceeds_events");
+        }
+        
+        // The intervals averaged into `iki_mean` all fall inside the window. Zero-length ones are
+        // left out of the mean, so only the intervals that can't all be zero are counted.
+        if let Some(meta) = self.meta.as_ref().filter(|meta| meta.window_ms > 0) {
+            let intervals = f.events.saturating_sub(f.paste_events).saturating_sub(1) as f64;
+            let kept = (intervals * (1.0 - zero_interval_share)).max(1.0);
+            if kept * f.iki_mean > meta.window_ms as f64 * (1.0 + CONSISTENCY_TOLERANCE) {
+                failed.push("exceeds_window");
+            }
+        }
+        
+        failed
+    }
+}
+
+/// Kind of a raw key event; key identities are never sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyEventKind {
+    Char,
+    Backspace,
+    Paste,
+}
+
+/// A key event timestamp relative to the start of the window
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KeyEvent {
+    /// Milliseconds since the first event of the window
+    pub t: u64,
+    
+    pub kind: KeyEventKind,
+}
+
+/// Payload carrying raw key-event timings, features are computed server-side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawTypingPayload {
+    /// Session identifier (UUID4)
+    pub session_id: Uuid,
+    
+    /// Timestamp when payload was created
+    pub ts: i64,
+    
+    /// Key events in the analysis window, oldest first
+    pub events: Vec<KeyEvent>,
+    
+    /// Optional metadata
+    pub meta: Option<TypingMeta>,
+    
+    /// Optional single-use client nonce, deduplicated instead of (`session_id`, `ts`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    
+    /// Challenge from `GET /challenge`, accepted once before it expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+}
+
+impl RawTypingPayload {
+    /// Validate the event list
+    pub fn validate(&self) -> Result<(), String> {
+        if self.events.is_empty() {
+            return Err("No events in payload".to_string());
+        }
+        
+        if self.events.len() > MAX_RAW_EVENTS {
+            return Err(format!("Too many events (max {})", MAX_RAW_EVENTS));
+        }
+        
+        if self.events.windows(2).any(|pair| pair[1].t < pair[0].t) {
+            return Err("Event timestamps must not decrease".to_string());
+        }
+        
+        Ok(())
+    }
+    
+    /// Extract features into a regular feature payload
+    pub fn to_feature_payload(&self) -> TypingFeaturePayload {
+        TypingFeaturePayload {
+            session_id: self.session_id,
+            ts: self.ts,
+            features: extraction::extract_features(&self.events),
+            meta: self.meta.clone(),
+            nonce: self.nonce.clone(),
+            challenge: self.challenge.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(features: TypingFeatures) -> TypingFeaturePayload {
+        TypingFeaturePayload::new(
+            features,
+            Some(TypingMeta {
+                window_ms: 5000,
+                locale: "en_US".to_string(),
+                platform: "web".to_string(),
+                app_ver: "1.0.0".to_string(),
+            }),
+        )
+    }
+
+    fn features() -> TypingFeatures {
+        TypingFeatures {
+            events: 20,
+            iki_mean: 150.0,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: -0.5,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_consistent_features_pass() {
+        assert!(payload(features()).inconsistencies(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_forged_aggregates_flagged() {
+        let forged = TypingFeatures {
+            events: 80,
+            iki_iqr: 400.0,
+            burstiness: 0.3,
+            entropy: 3.9,
+            outlier_ratio: 0.2,
+            ..features()
+        };
+
+        assert_eq!(
+            payload(forged).inconsistencies(0.0),
+            vec![
+                "iqr_exceeds_std",
+                "burstiness_mismatch",
+                "outlier_ratio_impossible",
+                "entropy_impossible",
+                "exceeds_window"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extracted_features_are_consistent() {
+        let events: Vec<KeyEvent> = [0, 90, 400, 460, 1200, 1290, 1350, 1800, 1830, 2600]
+            .iter()
+            .map(|&t| KeyEvent { t, kind: KeyEventKind::Char })
+            .collect();
+        let raw = RawTypingPayload {
+            session_id: Uuid::new_v4(),
+            ts: 0,
+            events,
+            meta: None,
+            nonce: None,
+            challenge: None,
+        };
+
+        assert!(raw.to_feature_payload().inconsistencies(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_zero_mean_only_from_extracted_pastes() {
+        let events = vec![
+            KeyEvent { t: 0, kind: KeyEventKind::Paste },
+            KeyEvent { t: 800, kind: KeyEventKind::Paste },
+        ];
+        let raw = RawTypingPayload {
+            session_id: Uuid::new_v4(),
+            ts: 0,
+            events,
+            meta: None,
+            nonce: None,
+            challenge: None,
+        };
+        let pasted = raw.to_feature_payload();
+
+        assert!(pasted.validate_extracted().is_ok());
+        // Clients can't claim the same
+        assert!(pasted.validate().is_err());
+    }
+
+    #[test]
+    fn test_duplicate_timestamps_fit_the_window() {
+        // A coarse timer stamps every other key with its predecessor's millisecond
+        let events: Vec<KeyEvent> = (0..40)
+            .map(|i| KeyEvent {
+                t: (i / 2) * 250,
+                kind: KeyEventKind::Char,
+            })
+            .collect();
+        let mut payload = RawTypingPayload {
+            session_id: Uuid::new_v4(),
+            ts: 0,
+            events,
+            meta: None,
+            nonce: None,
+            challenge: None,
+        }
+        .to_feature_payload();
+        payload.meta = Some(TypingMeta {
+            window_ms: 19 * 250,
+            locale: "en_US".to_string(),
+            platform: "web".to_string(),
+            app_ver: "1.0.0".to_string(),
+        });
+
+        assert_eq!(payload.features.iki_mean, 250.0);
+        assert_eq!(payload.inconsistencies(0.0), vec!["exceeds_window"]);
+        assert!(payload.inconsistencies(0.5).is_empty(), "{:?}", payload.inconsistencies(0.5));
+    }
 }
@@ -1,10 +1,206 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::features::{FeatureScore, TypingFeatures};
+
+/// Recommended handling of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Allow,
+    Challenge,
+    Block,
+}
+
+/// A score range mapped to a label and an action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionBand {
+    /// Label returned for scores in this band
+    pub name: String,
+    
+    /// Lowest score in the band, the band extends up to the next one
+    pub min_score: f64,
+    
+    /// What the caller should do with the session
+    pub action: Action,
+}
+
+impl DecisionBand {
+    /// The two bands implied by a single suspicious threshold
+    pub fn from_threshold(suspicious_threshold: f64) -> Vec<Self> {
+        vec![
+            Self {
+                name: "likely_human".to_string(),
+                min_score: suspicious_threshold,
+                action: Action::Allow,
+            },
+            Self {
+                name: "suspicious".to_string(),
+                min_score: 0.0,
+                action: Action::Challenge,
+            },
+        ]
+    }
+    
+    /// Check bands are named, unique, descending and cover every score down to 0.0
+    pub fn validate_all(bands: &[Self]) -> Result<(), String> {
+        for (i, band) in bands.iter().enumerate() {
+            if band.name.is_empty() || band.name == INSUFFICIENT_DATA {
+                return Err(format!("Band name '{}' is empty or reserved", band.name));
+            }
+            if bands[..i].iter().any(|other| other.name == band.name) {
+                return Err(format!("Duplicate band name: {}", band.name));
+            }
+            if !(0.0..=1.0).contains(&band.min_score) {
+                return Err(format!("min_score of band '{}' must be between 0.0 and 1.0", band.name));
+            }
+            if i > 0 && band.min_score >= bands[i - 1].min_score {
+                return Err(format!("Bands must be ordered by descending min_score, '{}' is out of order", band.name));
+            }
+        }
+        
+        match bands.last() {
+            Some(last) if last.min_score == 0.0 => Ok(()),
+            Some(last) => Err(format!("Last band '{}' must have min_score 0.0", last.name)),
+            None => Err("At least one band is required".to_string()),
+        }
+    }
+}
+
+/// Label used when the window is too short to judge
+pub const INSUFFICIENT_DATA: &str = "insufficient_data";
+
 /// Thresholds used for classification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Thresholds {
-    /// Score below which behavior is considered suspicious
+    /// Score below which behavior is not allowed
     pub suspicious_below: f64,
+    
+    /// Decision bands, highest first
+    #[serde(default)]
+    pub bands: Vec<DecisionBand>,
+}
+
+impl Thresholds {
+    /// Thresholds from validated bands
+    pub fn from_bands(bands: Vec<DecisionBand>) -> Self {
+        // Everything under the lowest allowed band is suspicious
+        let suspicious_below = bands
+            .iter()
+            .filter(|band| band.action == Action::Allow)
+            .map(|band| band.min_score)
+            .fold(f64::NAN, f64::min);
+        
+        Self {
+            suspicious_below: if suspicious_below.is_nan() { 1.0 } else { suspicious_below },
+            bands,
+        }
+    }
+    
+    /// Band containing a score
+    pub fn classify(&self, score: f64) -> Option<&DecisionBand> {
+        self.bands.iter().find(|band| score >= band.min_score)
+    }
+    
+    /// Label and action for a score
+    pub fn verdict(&self, score: f64) -> (String, Action) {
+        match self.classify(score) {
+            Some(band) => (band.name.clone(), band.action),
+            None => ("suspicious".to_string(), Action::Challenge),
+        }
+    }
+}
+
+/// How much the score can be trusted given the amount and spread of the data
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Confidence {
+    /// 0.0 (no information) to 1.0 (interval collapsed onto the score)
+    pub level: f64,
+    
+    /// Lower bound of the ~95% interval around the score
+    pub lower: f64,
+    
+    /// Upper bound of the ~95% interval around the score
+    pub upper: f64,
+}
+
+impl Confidence {
+    /// Estimate from the event count and the IKI coefficient of variation
+    pub fn estimate(score: f64, features: &TypingFeatures) -> Self {
+        // Scores average per-interval evidence, so the error shrinks with √n and
+        // grows with how dispersed the intervals are
+        let cv = if features.iki_mean > 0.0 {
+            features.iki_std / features.iki_mean
+        } else {
+            0.0
+        };
+        let events = f64::from(features.events.max(1));
+        let half_width = (1.96 * 0.5 * (1.0 + cv * cv).sqrt() / events.sqrt()).min(0.5);
+        
+        Self::around(score, half_width)
+    }
+    
+    /// The same interval width around another score
+    pub fn recentered(&self, score: f64) -> Self {
+        Self::around(score, (1.0 - self.level) / 2.0)
+    }
+    
+    fn around(score: f64, half_width: f64) -> Self {
+        Self {
+            level: 1.0 - 2.0 * half_width,
+            lower: (score - half_width).max(0.0),
+            upper: (score + half_width).min(1.0),
+        }
+    }
+}
+
+/// Distance of a session from the reference human population
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyReport {
+    /// Mahalanobis distance from the population mean
+    pub distance: f64,
+    
+    /// Share of human sessions at least this far from the mean
+    pub score: f64,
+}
+
+/// Experiment arm a session was assigned to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentArm {
+    pub experiment: String,
+    pub arm: String,
+}
+
+/// Output of one ensemble member
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemberScore {
+    /// Member name from the ensemble config
+    pub name: String,
+    
+    /// Engine of the member
+    pub engine: String,
+    
+    pub score: f64,
+    
+    pub weight: f64,
+}
+
+/// Score of a session across its recent windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionScore {
+    /// Exponentially weighted mean of the window scores, newest weighted most
+    pub score: f64,
+    
+    /// Windows the score covers
+    pub windows: usize,
+    
+    /// Key events across those windows
+    pub events: u32,
+    
+    /// Band containing the session score
+    pub label: String,
+    
+    /// Recommended action for that band
+    pub action: Action,
 }
 
 /// Server response with scoring results
@@ -13,53 +209,190 @@ pub struct ScoreResponse {
     /// Human-likeness score between 0.0 and 1.0
     pub score: f64,
     
-    /// Classification label
+    /// Scorer output before calibration, when a calibration is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_score: Option<f64>,
+    
+    /// Classification label, the name of the matching band
     pub label: String,
     
+    /// Recommended action for the matching band
+    pub action: Action,
+    
+    /// Interval around the score from the sample size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    
     /// Additional hints about the analysis
     pub hints: Vec<String>,
     
     /// Thresholds used for classification
     pub thresholds: Thresholds,
+    
+    /// Every ensemble member's score, when `scoring.ensemble.verbose` is set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<MemberScore>,
+    
+    /// Population anomaly, when a reference population is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anomaly: Option<AnomalyReport>,
+    
+    /// Per-feature sub-scores and contributions (heuristic engine only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breakdown: Vec<FeatureScore>,
+    
+    /// Scoring profile picked from the session metadata
+    #[serde(default)]
+    pub profile: String,
+    
+    /// Experiment arm that scored the session, when an experiment is running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experiment: Option<ExperimentArm>,
+    
+    /// Consistency checks the features failed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inconsistencies: Vec<String>,
+    
+    /// IDs of the hard rules that matched, in evaluation order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_rules: Vec<String>,
+    
+    /// Score over the session's recent windows, when sessions are tracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionScore>,
+    
+    /// Signed token of the verdict, when `attestation.enabled` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
 }
 
 impl ScoreResponse {
-    /// Create a new response
+    /// Create a new response with the default bands for a threshold
     pub fn new(score: f64, suspicious_threshold: f64) -> Self {
-        let label = if score >= suspicious_threshold {
-            "likely_human".to_string()
-        } else {
-            "suspicious".to_string()
-        };
+        Self::with_thresholds(score, Thresholds::from_bands(DecisionBand::from_threshold(suspicious_threshold)))
+    }
+    
+    /// Create a new response classified by decision bands
+    pub fn with_thresholds(score: f64, thresholds: Thresholds) -> Self {
+        let (label, action) = thresholds.verdict(score);
         
-        let hints = Self::generate_hints(score);
+        let hints = vec![Self::pattern_hint(score).to_string()];
         
         Self {
             score,
+            raw_score: None,
             label,
+            action,
+            confidence: None,
             hints,
-            thresholds: Thresholds {
-                suspicious_below: suspicious_threshold,
-            },
+            thresholds,
+            members: Vec::new(),
+            anomaly: None,
+            breakdown: Vec::new(),
+            profile: String::new(),
+            experiment: None,
+            inconsistencies: Vec::new(),
+            matched_rules: Vec::new(),
+            session: None,
+            attestation: None,
         }
     }
     
-    /// Generate hints based on score
-    fn generate_hints(score: f64) -> Vec<String> {
-        let mut hints = Vec::new();
-        
+    /// Overall hint based on score
+    fn pattern_hint(score: f64) -> &'static str {
         if score >= 0.8 {
-            hints.push("excellent_human_patterns".to_string());
+            "excellent_human_patterns"
         } else if score >= 0.6 {
-            hints.push("good_human_patterns".to_string());
+            "good_human_patterns"
         } else if score >= 0.4 {
-            hints.push("mixed_patterns".to_string());
+            "mixed_patterns"
         } else {
-            hints.push("suspicious_patterns".to_string());
+            "suspicious_patterns"
+        }
+    }
+    
+    /// Generate hints from the normalized sub-score of each feature
+    pub fn feature_hints(features: &TypingFeatures, sub_scores: &[(&str, f64)]) -> Vec<String> {
+        let mut hints = Vec::new();
+        
+        for &(feature, sub_score) in sub_scores {
+            let hint = match feature {
+                "speed" if sub_score < 0.5 && features.iki_mean < 100.0 => "too_fast",
+                "speed" if sub_score < 0.5 => "too_slow",
+                "variability" if sub_score >= 0.5 => "healthy_variability",
+                "variability" if sub_score < 0.3 => "low_variability",
+                "backspace" if features.backspace_per_100 < 1.0 => "no_corrections",
+                "backspace" if sub_score >= 1.0 => "some_backspaces",
+                "backspace" if sub_score <= 0.0 => "excessive_corrections",
+                "jitter" if features.jitter_mad < 5.0 => "metronomic_jitter",
+                "paste" if features.paste_events > 0 => "paste_detected",
+                _ => continue,
+            };
+            hints.push(hint.to_string());
         }
         
         hints
     }
+    
+    /// Flag features that fail the consistency checks, optionally forcing a suspicious verdict
+    pub fn mark_inconsistent(&mut self, failed: &[&str], force_suspicious: bool) {
+        self.hints.push("inconsistent_features".to_string());
+        self.inconsistencies = failed.iter().map(|check| check.to_string()).collect();
+        if force_suspicious {
+            let band = self.thresholds.bands.iter().find(|band| band.action != Action::Allow);
+            (self.label, self.action) = match band {
+                Some(band) => (band.name.clone(), band.action),
+                None => ("suspicious".to_string(), Action::Challenge),
+            };
+        }
+    }
+    
+    /// Lower the score of features already submitted by another session
+    pub fn mark_replayed(&mut self, penalty: f64) {
+        // A verdict forced by a rule or the event count stays as it is
+        let banded = self.thresholds.verdict(self.score).0 == self.label;
+        self.score = (self.score - penalty).max(0.0);
+        if banded {
+            (self.label, self.action) = self.thresholds.verdict(self.score);
+        }
+        // The interval width depends on the features only, so it moves with the score
+        self.confidence = self.confidence.map(|confidence| confidence.recentered(self.score));
+        // Dropped for insufficient data, otherwise follows the lowered score
+        if let Some(hint) = self.hints.iter_mut().find(|hint| hint.ends_with("_patterns")) {
+            *hint = Self::pattern_hint(self.score).to_string();
+        }
+        self.hints.push("replayed_features".to_string());
+    }
+    
+    /// Replace the verdict when the window is too short to judge
+    pub fn mark_insufficient_data(&mut self) {
+        self.label = INSUFFICIENT_DATA.to_string();
+        self.action = Action::Challenge;
+        self.hints.retain(|hint| !hint.ends_with("_patterns"));
+        self.hints.insert(0, "too_few_events".to_string());
+    }
+}
+
+/// Body of every error response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    /// Error type, the HTTP reason phrase
+    pub error: String,
+    
+    /// Human-readable description
+    pub message: String,
+    
+    /// Stable machine-readable code, e.g. `VALIDATION_ERROR`
+    pub code: String,
+}
+
+/// A challenge to include in the next scoring payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeResponse {
+    pub challenge: String,
+    
+    /// Unix time in ms after which the challenge is rejected
+    pub expires_at: i64,
 }
 
 /// Health check response
@@ -83,6 +416,12 @@ impl HealthResponse {
 /// Configuration response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigResponse {
+    pub engine: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<String>,
+    pub min_events: u32,
     pub default_thresholds: Thresholds,
     pub feature_weights: crate::models::features::FeatureWeights,
     pub rate_limits: RateLimitConfig,
@@ -103,3 +442,124 @@ impl Default for RateLimitConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::features::NormalizationCurves;
+
+    fn features() -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean: 150.0,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: 0.2,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.08,
+        }
+    }
+
+    fn hints(features: &TypingFeatures) -> Vec<String> {
+        ScoreResponse::feature_hints(features, &features.sub_scores(&NormalizationCurves::default()))
+    }
+
+    #[test]
+    fn test_label_from_threshold() {
+        assert_eq!(ScoreResponse::new(0.9, 0.4).label, "likely_human");
+        assert_eq!(ScoreResponse::new(0.3, 0.4).label, "suspicious");
+    }
+
+    fn band(name: &str, min_score: f64, action: Action) -> DecisionBand {
+        DecisionBand {
+            name: name.to_string(),
+            min_score,
+            action,
+        }
+    }
+
+    #[test]
+    fn test_four_bands() {
+        let bands = vec![
+            band("human", 0.7, Action::Allow),
+            band("uncertain", 0.4, Action::Allow),
+            band("suspicious", 0.2, Action::Challenge),
+            band("bot", 0.0, Action::Block),
+        ];
+        assert!(DecisionBand::validate_all(&bands).is_ok());
+        let thresholds = Thresholds::from_bands(bands);
+        assert_eq!(thresholds.suspicious_below, 0.4);
+
+        let response = ScoreResponse::with_thresholds(0.1, thresholds.clone());
+        assert_eq!((response.label.as_str(), response.action), ("bot", Action::Block));
+        let response = ScoreResponse::with_thresholds(0.5, thresholds);
+        assert_eq!((response.label.as_str(), response.action), ("uncertain", Action::Allow));
+    }
+
+    #[test]
+    fn test_invalid_bands_rejected() {
+        let unordered = [band("a", 0.3, Action::Allow), band("b", 0.5, Action::Block), band("c", 0.0, Action::Block)];
+        assert!(DecisionBand::validate_all(&unordered).is_err());
+        assert!(DecisionBand::validate_all(&[band("a", 0.3, Action::Allow)]).is_err());
+        assert!(DecisionBand::validate_all(&[]).is_err());
+    }
+
+    #[test]
+    fn test_human_hints() {
+        let hints = hints(&features());
+        assert!(hints.contains(&"healthy_variability".to_string()));
+        assert!(hints.contains(&"some_backspaces".to_string()));
+    }
+
+    #[test]
+    fn test_bot_hints() {
+        let bot = TypingFeatures {
+            iki_mean: 20.0,
+            backspace_per_100: 0.0,
+            paste_events: 3,
+            jitter_mad: 0.0,
+            ..features()
+        };
+        let hints = hints(&bot);
+        for hint in ["too_fast", "no_corrections", "metronomic_jitter", "paste_detected"] {
+            assert!(hints.contains(&hint.to_string()), "missing {}", hint);
+        }
+    }
+
+    #[test]
+    fn test_confidence_grows_with_events() {
+        let short = Confidence::estimate(0.3, &TypingFeatures { events: 8, ..features() });
+        let long = Confidence::estimate(0.3, &TypingFeatures { events: 500, ..features() });
+
+        assert!(short.level < 0.5);
+        assert!(long.level > 0.85);
+        assert!(short.upper - short.lower > long.upper - long.lower);
+        assert!(short.lower >= 0.0 && short.upper <= 1.0);
+        assert!(long.lower < 0.3 && long.upper > 0.3);
+    }
+
+    #[test]
+    fn test_replay_penalty() {
+        let mut response = ScoreResponse::new(0.6, 0.4);
+        response.confidence = Some(Confidence {
+            level: 0.8,
+            lower: 0.5,
+            upper: 0.7,
+        });
+        response.mark_replayed(0.3);
+        assert!((response.score - 0.3).abs() < 1e-9);
+        assert_eq!((response.label.as_str(), response.action), ("suspicious", Action::Challenge));
+        assert_eq!(response.hints, vec!["suspicious_patterns", "replayed_features"]);
+        let confidence = response.confidence.unwrap();
+        assert!((confidence.lower - 0.2).abs() < 1e-9 && (confidence.upper - 0.4).abs() < 1e-9);
+
+        // A forced verdict isn't re-derived from the lowered score
+        let mut short = ScoreResponse::new(0.6, 0.4);
+        short.mark_insufficient_data();
+        short.mark_replayed(0.3);
+        assert_eq!(short.label, INSUFFICIENT_DATA);
+    }
+}
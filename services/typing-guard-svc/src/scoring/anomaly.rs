@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{features::TypingFeatures, response::AnomalyReport},
+    scoring::{Scorer, ScoringError},
+};
+
+/// Reference human population as stored in a population file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopulationModel {
+    /// Population version, reported by `/config`
+    pub version: String,
+
+    /// `TypingFeatures` fields the statistics cover
+    pub features: Vec<String>,
+
+    /// Mean of each field over human sessions
+    pub mean: Vec<f64>,
+
+    /// Covariance matrix of the fields, row-major
+    pub covariance: Vec<Vec<f64>>,
+}
+
+/// Scores sessions by how typical their joint features are of the human population
+pub struct AnomalyScorer {
+    version: String,
+    /// `TypingFeatures::values` index of each dimension
+    indices: Vec<usize>,
+    mean: Vec<f64>,
+    /// Lower-triangular Cholesky factor of the covariance
+    cholesky: Vec<Vec<f64>>,
+}
+
+impl AnomalyScorer {
+    pub const NAME: &'static str = "anomaly";
+
+    /// Check the population and factor its covariance
+    pub fn new(model: PopulationModel) -> Result<Self, ScoringError> {
+        let dims = model.features.len();
+        if dims == 0 {
+            return Err(ScoringError::InvalidModel("population needs at least one feature".to_string()));
+        }
+        if model.mean.len() != dims
+            || model.covariance.len() != dims
+            || model.covariance.iter().any(|row| row.len() != dims)
+        {
+            return Err(ScoringError::InvalidModel(format!(
+                "mean and covariance must match the {} features",
+                dims
+            )));
+        }
+        if model.mean.iter().chain(model.covariance.iter().flatten()).any(|v| !v.is_finite()) {
+            return Err(ScoringError::InvalidModel("population statistics must be finite".to_string()));
+        }
+
+        let indices = model
+            .features
+            .iter()
+            .map(|name| TypingFeatures::field_index(name).ok_or_else(|| ScoringError::UnknownFeature(name.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cholesky = cholesky(&model.covariance).ok_or_else(|| {
+            ScoringError::InvalidModel("covariance must be symmetric positive definite".to_string())
+        })?;
+
+        Ok(Self {
+            version: model.version,
+            indices,
+            mean: model.mean,
+            cholesky,
+        })
+    }
+
+    /// Load a population from a JSON or TOML file
+    pub fn from_file(path: &str) -> Result<Self, ScoringError> {
+        Self::new(super::load_model_file(path)?)
+    }
+
+    /// Mahalanobis distance of a session from the population mean
+    pub fn distance(&self, features: &TypingFeatures) -> f64 {
+        let values = features.values();
+
+        // Solve L·y = x - μ; the squared distance is |y|²
+        let mut y = vec![0.0; self.mean.len()];
+        for i in 0..y.len() {
+            let centered = values[self.indices[i]] - self.mean[i];
+            let known: f64 = (0..i).map(|j| self.cholesky[i][j] * y[j]).sum();
+            y[i] = (centered - known) / self.cholesky[i][i];
+        }
+
+        y.iter().map(|v| v * v).sum::<f64>().sqrt()
+    }
+
+    /// Distance and the matching typicality score
+    pub fn report(&self, features: &TypingFeatures) -> AnomalyReport {
+        let distance = self.distance(features);
+        AnomalyReport {
+            distance,
+            score: chi_square_survival(distance * distance, self.mean.len()),
+        }
+    }
+}
+
+impl Scorer for AnomalyScorer {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(&self.version)
+    }
+
+    /// Share of human sessions lying at least this far from the mean
+    fn score(&self, features: &TypingFeatures) -> f64 {
+        self.report(features).score
+    }
+}
+
+/// Cholesky factor of a symmetric positive definite matrix
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut lower = vec![vec![0.0; n]; n];
+
+    for i in 0..n {
+        for j in 0..=i {
+            if (matrix[i][j] - matrix[j][i]).abs() > 1e-9 * matrix[i][j].abs().max(1.0) {
+                return None;
+            }
+            let sum: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+            if i == j {
+                let diagonal = matrix[i][i] - sum;
+                if diagonal <= 0.0 {
+                    return None;
+                }
+                lower[i][j] = diagonal.sqrt();
+            } else {
+                lower[i][j] = (matrix[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+
+    Some(lower)
+}
+
+/// P(χ²ₖ ≥ x), via the Wilson–Hilferty normal approximation
+fn chi_square_survival(x: f64, k: usize) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let k = k as f64;
+    let variance = 2.0 / (9.0 * k);
+    let z = ((x / k).cbrt() - (1.0 - variance)) / variance.sqrt();
+    (0.5 * erfc(z / std::f64::consts::SQRT_2)).clamp(0.0, 1.0)
+}
+
+/// Complementary error function (Abramowitz & Stegun 7.1.26, error < 1.5e-7)
+fn erfc(x: f64) -> f64 {
+    if x < 0.0 {
+        return 2.0 - erfc(-x);
+    }
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    poly * (-x * x).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn population() -> AnomalyScorer {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/models/population-example.json");
+        AnomalyScorer::from_file(path).unwrap()
+    }
+
+    fn human() -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean: 180.0,
+            iki_std: 90.0,
+            iki_iqr: 80.0,
+            burstiness: -0.33,
+            entropy: 2.6,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 45.0,
+            outlier_ratio: 0.03,
+        }
+    }
+
+    #[test]
+    fn test_chi_square_survival() {
+        // Median of χ²₂ is 2 ln 2, 95th percentile of χ²₆ is 12.59
+        assert!((chi_square_survival(2.0 * 2f64.ln(), 2) - 0.5).abs() < 0.01);
+        assert!((chi_square_survival(12.59, 6) - 0.05).abs() < 0.005);
+        assert_eq!(chi_square_survival(0.0, 6), 1.0);
+    }
+
+    #[test]
+    fn test_distance_grows_away_from_population() {
+        let scorer = population();
+        let typical = scorer.report(&human());
+
+        assert!(typical.distance < 2.0);
+        assert!(typical.score > 0.5);
+
+        // Each feature tuned into its "optimal" range, but with far less jitter
+        // and burstiness than such a spread of intervals produces in humans
+        let tuned_bot = TypingFeatures {
+            iki_std: 95.0,
+            burstiness: -0.9,
+            jitter_mad: 10.0,
+            ..human()
+        };
+        let odd = scorer.report(&tuned_bot);
+        assert!(odd.distance > 4.0);
+        assert!(odd.score < 0.01);
+    }
+
+    #[test]
+    fn test_non_positive_definite_rejected() {
+        let model = PopulationModel {
+            version: "bad".to_string(),
+            features: vec!["iki_mean".to_string(), "iki_std".to_string()],
+            mean: vec![180.0, 90.0],
+            covariance: vec![vec![1.0, 2.0], vec![2.0, 1.0]],
+        };
+
+        assert!(matches!(AnomalyScorer::new(model), Err(ScoringError::InvalidModel(_))));
+    }
+}
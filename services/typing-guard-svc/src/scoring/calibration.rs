@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{models::features::Curve, scoring::ScoringError};
+
+/// Maps a raw scorer output to a calibrated probability of being human
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Calibration {
+    /// Platt scaling: p = 1 / (1 + exp(a * score + b))
+    Platt { a: f64, b: f64 },
+
+    /// Isotonic regression as sorted `[raw_score, probability]` points, interpolated linearly.
+    /// The file's `points` are read straight into a piecewise curve, built once per load.
+    Isotonic {
+        #[serde(rename = "points", with = "piecewise_points")]
+        curve: Curve,
+    },
+}
+
+impl Calibration {
+    /// Load calibration parameters from a JSON or TOML file
+    pub fn from_file(path: &str) -> Result<Self, ScoringError> {
+        let calibration: Self = super::load_model_file(path)?;
+        calibration.validate().map_err(ScoringError::InvalidModel)?;
+        Ok(calibration)
+    }
+
+    /// Method name, as used in the parameter file
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::Platt { .. } => "platt",
+            Self::Isotonic { .. } => "isotonic",
+        }
+    }
+
+    /// Calibrated probability for a raw score
+    pub fn apply(&self, raw_score: f64) -> f64 {
+        match self {
+            Self::Platt { a, b } => 1.0 / (1.0 + (a * raw_score + b).exp()),
+            Self::Isotonic { curve } => curve.eval(raw_score),
+        }
+    }
+
+    /// Check the parameters are usable
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Platt { a, b } if !a.is_finite() || !b.is_finite() => {
+                Err("Platt parameters must be finite".to_string())
+            }
+            Self::Platt { .. } => Ok(()),
+            Self::Isotonic { curve } => curve.validate("calibration"),
+        }
+    }
+
+    /// Fit Platt scaling by Newton's method, with Platt's smoothed targets
+    pub fn fit_platt(samples: &[(f64, bool)]) -> Self {
+        let positives = samples.iter().filter(|(_, human)| *human).count() as f64;
+        let negatives = samples.len() as f64 - positives;
+        let high = (positives + 1.0) / (positives + 2.0);
+        let low = 1.0 / (negatives + 2.0);
+
+        let (mut a, mut b) = (0.0, ((negatives + 1.0) / (positives + 1.0)).ln());
+        for _ in 0..100 {
+            // Gradient and Hessian of the log-loss in (a, b)
+            let (mut ga, mut gb, mut haa, mut hab, mut hbb) = (0.0, 0.0, 1e-12, 0.0, 1e-12);
+            for &(score, human) in samples {
+                let target = if human { high } else { low };
+                let p = 1.0 / (1.0 + (a * score + b).exp());
+                let d = target - p;
+                let w = p * (1.0 - p);
+                ga += d * score;
+                gb += d;
+                haa += w * score * score;
+                hab += w * score;
+                hbb += w;
+            }
+
+            let det = haa * hbb - hab * hab;
+            if det.abs() < 1e-18 {
+                break;
+            }
+            let step_a = (hbb * ga - hab * gb) / det;
+            let step_b = (haa * gb - hab * ga) / det;
+            a -= step_a;
+            b -= step_b;
+            if step_a.abs() < 1e-10 && step_b.abs() < 1e-10 {
+                break;
+            }
+        }
+
+        Self::Platt { a, b }
+    }
+
+    /// Fit isotonic regression with pool-adjacent-violators
+    pub fn fit_isotonic(samples: &[(f64, bool)]) -> Self {
+        let mut sorted: Vec<(f64, f64)> = samples
+            .iter()
+            .map(|&(score, human)| (score, if human { 1.0 } else { 0.0 }))
+            .collect();
+        sorted.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        // Blocks of (min score, max score, label sum, count), merged until strictly increasing
+        let mut blocks: Vec<(f64, f64, f64, f64)> = Vec::new();
+        for (score, label) in sorted {
+            blocks.push((score, score, label, 1.0));
+            while blocks.len() > 1 {
+                let last = blocks[blocks.len() - 1];
+                let prev = blocks[blocks.len() - 2];
+                if prev.2 / prev.3 < last.2 / last.3 {
+                    break;
+                }
+                blocks.pop();
+                let merged = blocks.last_mut().unwrap();
+                *merged = (prev.0, last.1, prev.2 + last.2, prev.3 + last.3);
+            }
+        }
+
+        let mut points = Vec::new();
+        for (min, max, sum, count) in blocks {
+            let probability = sum / count;
+            points.push([min, probability]);
+            if max > min {
+                points.push([max, probability]);
+            }
+        }
+        if points.len() == 1 {
+            points.push(points[0]);
+        }
+
+        Self::Isotonic {
+            curve: Curve::Piecewise { points },
+        }
+    }
+}
+
+/// (De)serializes a piecewise `Curve` as its bare `[value, score]` points
+mod piecewise_points {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::models::features::Curve;
+
+    pub fn serialize<S: Serializer>(curve: &Curve, serializer: S) -> Result<S::Ok, S::Error> {
+        match curve {
+            Curve::Piecewise { points } => points.serialize(serializer),
+            Curve::Trapezoid { .. } => Err(serde::ser::Error::custom("expected a piecewise curve")),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Curve, D::Error> {
+        let points = Vec::<[f64; 2]>::deserialize(deserializer)?;
+        Ok(Curve::Piecewise { points })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<(f64, bool)> {
+        (0..200)
+            .map(|i| {
+                let score = i as f64 / 200.0;
+                // Humans get more likely as the score rises, with some overlap
+                (score, (i * 7919) % 200 < i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_platt_fit_is_increasing() {
+        let calibration = Calibration::fit_platt(&samples());
+        assert!(calibration.validate().is_ok());
+        assert!(calibration.apply(0.9) > calibration.apply(0.5));
+        assert!(calibration.apply(0.5) > calibration.apply(0.1));
+        assert!(calibration.apply(0.95) > 0.7);
+        assert!(calibration.apply(0.05) < 0.3);
+    }
+
+    #[test]
+    fn test_isotonic_fit_is_monotonic() {
+        let calibration = Calibration::fit_isotonic(&samples());
+        assert!(calibration.validate().is_ok());
+
+        let mut previous = 0.0;
+        for i in 0..=100 {
+            let p = calibration.apply(i as f64 / 100.0);
+            assert!(p >= previous - 1e-12);
+            assert!((0.0..=1.0).contains(&p));
+            previous = p;
+        }
+    }
+
+    #[test]
+    fn test_parse_parameter_file() {
+        let calibration: Calibration = toml::from_str("method = \"platt\"\na = -6.0\nb = 3.0\n").unwrap();
+        assert_eq!(calibration, Calibration::Platt { a: -6.0, b: 3.0 });
+        assert!((calibration.apply(0.5) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_isotonic_file_round_trip() {
+        let calibration = Calibration::fit_isotonic(&samples());
+        let json = serde_json::to_string(&calibration).unwrap();
+        assert!(json.starts_with("{\"method\":\"isotonic\",\"points\":[["));
+
+        let parsed: Calibration = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.method(), "isotonic");
+        for i in 0..=20 {
+            let score = i as f64 / 20.0;
+            assert!((parsed.apply(score) - calibration.apply(score)).abs() < 1e-12);
+        }
+    }
+}
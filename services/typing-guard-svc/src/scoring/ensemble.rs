@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::ScoringConfig,
+    models::{features::TypingFeatures, response::MemberScore},
+    scoring::{self, rules::RulesScorer, Scorer, ScoringError},
+};
+
+/// How member scores are merged into one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Combiner {
+    /// Weighted average of the member scores
+    #[default]
+    WeightedMean,
+
+    /// Most pessimistic member
+    Min,
+
+    /// Most optimistic member
+    Max,
+
+    /// Weighted share of members scoring at or above `vote_threshold`
+    Vote,
+}
+
+/// Members and combiner of the `ensemble` engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    #[serde(default)]
+    pub combiner: Combiner,
+
+    /// Score at which a member votes human, defaults to `scoring.suspicious_threshold`
+    #[serde(default)]
+    pub vote_threshold: Option<f64>,
+
+    /// Return every member's score in the response
+    #[serde(default)]
+    pub verbose: bool,
+
+    pub members: Vec<MemberConfig>,
+}
+
+/// One scorer of an ensemble
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberConfig {
+    /// Any engine but `ensemble`, or `rules` for the pre-stage hard rules
+    pub engine: String,
+
+    /// Name reported in the response, defaults to the engine
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Model file for model-based engines
+    #[serde(default)]
+    pub model_path: Option<String>,
+
+    #[serde(default = "default_member_weight")]
+    pub weight: f64,
+}
+
+fn default_member_weight() -> f64 {
+    1.0
+}
+
+impl MemberConfig {
+    /// Name reported in the response
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.engine)
+    }
+}
+
+impl EnsembleConfig {
+    /// Check members, weights and the vote threshold
+    pub fn validate(&self) -> Result<(), String> {
+        if self.members.is_empty() {
+            return Err("Ensemble needs at least one member".to_string());
+        }
+        for (i, member) in self.members.iter().enumerate() {
+            let engine = member.engine.as_str();
+            if engine == EnsembleScorer::NAME || !(scoring::is_known_engine(engine) || engine == RulesScorer::NAME) {
+                return Err(format!("Ensemble member engine '{}' is not allowed", engine));
+            }
+            if !member.weight.is_finite() || member.weight < 0.0 {
+                return Err(format!("Weight of ensemble member '{}' must be non-negative", engine));
+            }
+            // Names tell members apart in verbose responses
+            if self.members[..i].iter().any(|other| other.name() == member.name()) {
+                return Err(format!("Duplicate ensemble member name: {}", member.name()));
+            }
+        }
+        if self.members.iter().map(|member| member.weight).sum::<f64>() <= 0.0 {
+            return Err("Ensemble member weights must not all be zero".to_string());
+        }
+        if let Some(threshold) = self.vote_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err("Ensemble vote threshold must be between 0.0 and 1.0".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Member {
+    name: String,
+    weight: f64,
+    scorer: Arc<dyn Scorer>,
+}
+
+/// Combines several scorers into one
+pub struct EnsembleScorer {
+    combiner: Combiner,
+    vote_threshold: f64,
+    members: Vec<Member>,
+}
+
+impl EnsembleScorer {
+    pub const NAME: &'static str = "ensemble";
+
+    /// Build every member from the scoring config they share
+    pub fn new(config: &ScoringConfig) -> Result<Self, ScoringError> {
+        let ensemble = config
+            .ensemble
+            .as_ref()
+            .ok_or_else(|| ScoringError::InvalidModel("engine 'ensemble' needs scoring.ensemble".to_string()))?;
+
+        let members = ensemble
+            .members
+            .iter()
+            .map(|member| {
+                let scorer: Arc<dyn Scorer> = match member.engine.as_str() {
+                    RulesScorer::NAME => Arc::new(RulesScorer::new(&config.rules)),
+                    Self::NAME => return Err(ScoringError::InvalidModel("ensembles cannot be nested".to_string())),
+                    engine => scoring::build_scorer(&ScoringConfig {
+                        engine: engine.to_string(),
+                        model_path: member.model_path.clone(),
+                        ..config.clone()
+                    })?,
+                };
+                Ok(Member {
+                    name: member.name().to_string(),
+                    weight: member.weight,
+                    scorer,
+                })
+            })
+            .collect::<Result<Vec<_>, ScoringError>>()?;
+
+        Ok(Self {
+            combiner: ensemble.combiner,
+            vote_threshold: ensemble.vote_threshold.unwrap_or(config.suspicious_threshold),
+            members,
+        })
+    }
+}
+
+impl Scorer for EnsembleScorer {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn score(&self, features: &TypingFeatures) -> f64 {
+        self.combine(&self.members(features))
+    }
+
+    fn members(&self, features: &TypingFeatures) -> Vec<MemberScore> {
+        self.members
+            .iter()
+            .map(|member| MemberScore {
+                name: member.name.clone(),
+                engine: member.scorer.name().to_string(),
+                score: member.scorer.score(features),
+                weight: member.weight,
+            })
+            .collect()
+    }
+
+    fn score_with_members(&self, features: &TypingFeatures) -> (f64, Vec<MemberScore>) {
+        let members = self.members(features);
+        (self.combine(&members), members)
+    }
+}
+
+impl EnsembleScorer {
+    /// Merge member scores with the configured combiner
+    fn combine(&self, scores: &[MemberScore]) -> f64 {
+        let total_weight: f64 = scores.iter().map(|member| member.weight).sum();
+
+        match self.combiner {
+            Combiner::WeightedMean => scores.iter().map(|m| m.score * m.weight).sum::<f64>() / total_weight,
+            Combiner::Min => scores.iter().map(|m| m.score).fold(1.0, f64::min),
+            Combiner::Max => scores.iter().map(|m| m.score).fold(0.0, f64::max),
+            Combiner::Vote => {
+                scores
+                    .iter()
+                    .filter(|m| m.score >= self.vote_threshold)
+                    .map(|m| m.weight)
+                    .sum::<f64>()
+                    / total_weight
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(iki_mean: f64) -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: -0.5,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.08,
+        }
+    }
+
+    fn config(combiner: Combiner) -> ScoringConfig {
+        let logistic = concat!(env!("CARGO_MANIFEST_DIR"), "/config/models/logistic-example.json");
+        ScoringConfig {
+            engine: EnsembleScorer::NAME.to_string(),
+            ensemble: Some(EnsembleConfig {
+                combiner,
+                vote_threshold: Some(0.5),
+                verbose: true,
+                members: vec![
+                    MemberConfig {
+                        engine: "heuristic".to_string(),
+                        name: None,
+                        model_path: None,
+                        weight: 3.0,
+                    },
+                    MemberConfig {
+                        engine: "logistic".to_string(),
+                        name: Some("candidate".to_string()),
+                        model_path: Some(logistic.to_string()),
+                        weight: 1.0,
+                    },
+                ],
+            }),
+            ..ScoringConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_combiners() {
+        let features = features(150.0);
+        let members = EnsembleScorer::new(&config(Combiner::WeightedMean)).unwrap().members(&features);
+        assert_eq!(members.len(), 2);
+        assert_eq!((members[1].name.as_str(), members[1].engine.as_str()), ("candidate", "logistic"));
+        let (a, b) = (members[0].score, members[1].score);
+
+        let score = |combiner| EnsembleScorer::new(&config(combiner)).unwrap().score(&features);
+        assert!((score(Combiner::WeightedMean) - (3.0 * a + b) / 4.0).abs() < 1e-9);
+        assert_eq!(score(Combiner::Min), a.min(b));
+        assert_eq!(score(Combiner::Max), a.max(b));
+
+        let expected_vote = [(a, 3.0), (b, 1.0)]
+            .iter()
+            .filter(|(s, _)| *s >= 0.5)
+            .map(|(_, w)| w)
+            .sum::<f64>()
+            / 4.0;
+        assert_eq!(score(Combiner::Vote), expected_vote);
+    }
+
+    #[test]
+    fn test_nested_ensemble_rejected() {
+        let mut config = config(Combiner::Min);
+        let ensemble = config.ensemble.as_mut().unwrap();
+        ensemble.members[0].engine = EnsembleScorer::NAME.to_string();
+
+        assert!(ensemble.validate().is_err());
+        assert!(EnsembleScorer::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_member_names_rejected() {
+        let mut config = config(Combiner::Min);
+        let ensemble = config.ensemble.as_mut().unwrap();
+        assert!(ensemble.validate().is_ok());
+
+        ensemble.members[1].name = Some("heuristic".to_string());
+        assert_eq!(ensemble.validate().unwrap_err(), "Duplicate ensemble member name: heuristic");
+    }
+
+    #[test]
+    fn test_score_with_members_matches_score() {
+        let features = features(150.0);
+        let scorer = EnsembleScorer::new(&config(Combiner::WeightedMean)).unwrap();
+        let (score, members) = scorer.score_with_members(&features);
+
+        assert_eq!(score, scorer.score(&features));
+        assert_eq!(members.len(), 2);
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    config::ScoringConfig,
+    models::response::ExperimentArm,
+    scoring::{ScoringError, ScoringPipeline},
+};
+
+/// A/B experiment splitting sessions between scoring configurations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    /// Experiment name, also salts the assignment hash
+    pub name: String,
+
+    pub arms: Vec<ArmConfig>,
+}
+
+/// One arm of an experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmConfig {
+    /// Arm name, recorded in responses and logs
+    pub name: String,
+
+    /// Relative share of sessions assigned to the arm
+    #[serde(default = "default_arm_weight")]
+    pub weight: f64,
+
+    /// Complete scoring settings of the arm, the live `[scoring]` when absent
+    #[serde(default)]
+    pub scoring: Option<ScoringConfig>,
+}
+
+fn default_arm_weight() -> f64 {
+    1.0
+}
+
+impl ExperimentConfig {
+    /// Check arm names, weights and scoring settings
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Experiment name cannot be empty".to_string());
+        }
+        if self.arms.is_empty() {
+            return Err(format!("Experiment '{}' needs at least one arm", self.name));
+        }
+        for (i, arm) in self.arms.iter().enumerate() {
+            if arm.name.is_empty() || self.arms[..i].iter().any(|other| other.name == arm.name) {
+                return Err(format!("Arm name '{}' is empty or duplicated", arm.name));
+            }
+            if !arm.weight.is_finite() || arm.weight < 0.0 {
+                return Err(format!("Weight of arm '{}' must be non-negative", arm.name));
+            }
+            if let Some(scoring) = &arm.scoring {
+                scoring.validate().map_err(|e| format!("Arm '{}': {}", arm.name, e))?;
+            }
+        }
+        if self.arms.iter().map(|arm| arm.weight).sum::<f64>() <= 0.0 {
+            return Err(format!("Arm weights of experiment '{}' must not all be zero", self.name));
+        }
+        Ok(())
+    }
+}
+
+/// An arm with its pipeline
+pub struct Arm {
+    pub name: String,
+    /// Upper end of the arm's slice of [0, 1)
+    cumulative_share: f64,
+    /// Own pipeline, or `None` to use the live one
+    pub pipeline: Option<ScoringPipeline>,
+}
+
+/// Assigns sessions to arms deterministically from their `session_id`
+pub struct Experiment {
+    pub name: String,
+    arms: Vec<Arm>,
+}
+
+impl Experiment {
+    /// Build the pipelines of every arm
+    pub fn new(config: &ExperimentConfig) -> Result<Self, ScoringError> {
+        let total: f64 = config.arms.iter().map(|arm| arm.weight).sum();
+        let mut cumulative = 0.0;
+        let arms = config
+            .arms
+            .iter()
+            .map(|arm| {
+                cumulative += arm.weight / total;
+                Ok(Arm {
+                    name: arm.name.clone(),
+                    cumulative_share: cumulative,
+                    pipeline: arm.scoring.as_ref().map(ScoringPipeline::new).transpose()?,
+                })
+            })
+            .collect::<Result<Vec<_>, ScoringError>>()?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            arms,
+        })
+    }
+
+    /// Arm of a session; the same session always lands in the same arm
+    pub fn assign(&self, session_id: &Uuid) -> &Arm {
+        let digest = Sha256::new()
+            .chain_update(self.name.as_bytes())
+            .chain_update(b":")
+            .chain_update(session_id.as_bytes())
+            .finalize();
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        let position = u64::from_be_bytes(bytes) as f64 / (u64::MAX as f64 + 1.0);
+
+        self.arms
+            .iter()
+            .find(|arm| position < arm.cumulative_share)
+            .unwrap_or(&self.arms[self.arms.len() - 1])
+    }
+
+    /// Experiment and arm names, as recorded in the response
+    pub fn label(&self, arm: &Arm) -> ExperimentArm {
+        ExperimentArm {
+            experiment: self.name.clone(),
+            arm: arm.name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(weights: &[f64]) -> Experiment {
+        let config = ExperimentConfig {
+            name: "weights-2025".to_string(),
+            arms: weights
+                .iter()
+                .enumerate()
+                .map(|(i, &weight)| ArmConfig {
+                    name: format!("arm{}", i),
+                    weight,
+                    scoring: None,
+                })
+                .collect(),
+        };
+        config.validate().unwrap();
+        Experiment::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_assignment_is_deterministic() {
+        let experiment = experiment(&[1.0, 1.0]);
+        let session = Uuid::new_v4();
+        let first = experiment.assign(&session).name.clone();
+        for _ in 0..10 {
+            assert_eq!(experiment.assign(&session).name, first);
+        }
+    }
+
+    #[test]
+    fn test_assignment_follows_weights() {
+        let experiment = experiment(&[3.0, 1.0]);
+        let in_first = (0..4000)
+            .filter(|_| experiment.assign(&Uuid::new_v4()).name == "arm0")
+            .count();
+
+        // 75% expected, binomial standard deviation ~0.7%
+        assert!((2850..=3150).contains(&in_first), "{} of 4000", in_first);
+    }
+}
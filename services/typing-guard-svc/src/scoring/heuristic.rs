@@ -0,0 +1,32 @@
+use crate::{
+    models::features::{FeatureScore, FeatureWeights, NormalizationCurves, TypingFeatures},
+    scoring::Scorer,
+};
+
+/// The original weighted heuristic: normalized features times feature weights
+pub struct HeuristicScorer {
+    weights: FeatureWeights,
+    normalization: NormalizationCurves,
+}
+
+impl HeuristicScorer {
+    pub const NAME: &'static str = "heuristic";
+
+    pub fn new(weights: FeatureWeights, normalization: NormalizationCurves) -> Self {
+        Self { weights, normalization }
+    }
+}
+
+impl Scorer for HeuristicScorer {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn score(&self, features: &TypingFeatures) -> f64 {
+        features.calculate_score(&self.weights, &self.normalization)
+    }
+
+    fn breakdown(&self, features: &TypingFeatures) -> Vec<FeatureScore> {
+        features.breakdown(&self.weights, &self.normalization)
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::features::TypingFeatures,
+    scoring::{Scorer, ScoringError},
+};
+
+/// Logistic regression model as stored in a model file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticModel {
+    /// Model version, reported by `/config`
+    pub version: String,
+
+    /// Bias term added before the sigmoid
+    pub intercept: f64,
+
+    /// One term per `TypingFeatures` field used by the model
+    pub terms: Vec<LogisticTerm>,
+}
+
+/// A standardized feature and its coefficient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticTerm {
+    /// `TypingFeatures` field name
+    pub feature: String,
+
+    /// Coefficient applied to the standardized value
+    pub coefficient: f64,
+
+    /// Population mean used for standardization
+    #[serde(default)]
+    pub mean: f64,
+
+    /// Population standard deviation used for standardization
+    #[serde(default = "default_std")]
+    pub std: f64,
+}
+
+fn default_std() -> f64 {
+    1.0
+}
+
+/// Scores sessions with a trained logistic regression, returning P(human)
+pub struct LogisticScorer {
+    model: LogisticModel,
+    /// `TypingFeatures::values` index of each term
+    indices: Vec<usize>,
+}
+
+impl LogisticScorer {
+    pub const NAME: &'static str = "logistic";
+
+    /// Check the model and resolve its feature names
+    pub fn new(model: LogisticModel) -> Result<Self, ScoringError> {
+        if !model.intercept.is_finite() {
+            return Err(ScoringError::InvalidModel("intercept must be finite".to_string()));
+        }
+
+        let mut indices = Vec::with_capacity(model.terms.len());
+        for term in &model.terms {
+            let index = TypingFeatures::field_index(&term.feature)
+                .ok_or_else(|| ScoringError::UnknownFeature(term.feature.clone()))?;
+            if !term.coefficient.is_finite() || !term.mean.is_finite() {
+                return Err(ScoringError::InvalidModel(format!(
+                    "coefficient and mean of '{}' must be finite",
+                    term.feature
+                )));
+            }
+            if !term.std.is_finite() || term.std <= 0.0 {
+                return Err(ScoringError::InvalidModel(format!(
+                    "std of '{}' must be greater than 0",
+                    term.feature
+                )));
+            }
+            indices.push(index);
+        }
+
+        Ok(Self { model, indices })
+    }
+
+    /// Load a model from a JSON or TOML file
+    pub fn from_file(path: &str) -> Result<Self, ScoringError> {
+        Self::new(super::load_model_file(path)?)
+    }
+}
+
+impl Scorer for LogisticScorer {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(&self.model.version)
+    }
+
+    fn score(&self, features: &TypingFeatures) -> f64 {
+        let values = features.values();
+        let logit = self
+            .model
+            .terms
+            .iter()
+            .zip(&self.indices)
+            .fold(self.model.intercept, |logit, (term, &index)| {
+                logit + term.coefficient * (values[index] - term.mean) / term.std
+            });
+
+        1.0 / (1.0 + (-logit).exp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(iki_mean: f64) -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: -0.5,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.08,
+        }
+    }
+
+    #[test]
+    fn test_logistic_probability() {
+        let model: LogisticModel = serde_json::from_str(
+            r#"{
+                "version": "test-1",
+                "intercept": 0.0,
+                "terms": [{ "feature": "iki_mean", "coefficient": 1.0, "mean": 150.0, "std": 50.0 }]
+            }"#,
+        )
+        .unwrap();
+        let scorer = LogisticScorer::new(model).unwrap();
+
+        assert!((scorer.score(&features(150.0)) - 0.5).abs() < 1e-9);
+        assert!(scorer.score(&features(200.0)) > 0.7);
+        assert!(scorer.score(&features(100.0)) < 0.3);
+        assert_eq!(scorer.version(), Some("test-1"));
+    }
+
+    #[test]
+    fn test_unknown_feature_rejected() {
+        let model: LogisticModel = toml::from_str(
+            r#"
+            version = "test-2"
+            intercept = 0.5
+
+            [[terms]]
+            feature = "mouse_speed"
+            coefficient = 1.0
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            LogisticScorer::new(model),
+            Err(ScoringError::UnknownFeature(name)) if name == "mouse_speed"
+        ));
+    }
+
+    #[test]
+    fn test_example_model_loads() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/models/logistic-example.json");
+        let scorer = LogisticScorer::from_file(path).unwrap();
+        let score = scorer.score(&features(150.0));
+        assert!(score > 0.0 && score < 1.0);
+    }
+}
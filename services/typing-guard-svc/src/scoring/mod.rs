@@ -0,0 +1,162 @@
+use std::{path::Path, sync::Arc};
+
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::{
+    config::ScoringConfig,
+    models::{
+        features::{FeatureScore, TypingFeatures},
+        response::MemberScore,
+    },
+};
+
+pub mod anomaly;
+pub mod calibration;
+pub mod ensemble;
+pub mod experiment;
+pub mod heuristic;
+pub mod logistic;
+pub mod pipeline;
+pub mod replay;
+pub mod rules;
+pub mod session;
+pub mod shadow;
+pub mod trees;
+
+pub use anomaly::AnomalyScorer;
+pub use calibration::Calibration;
+pub use ensemble::EnsembleScorer;
+pub use experiment::Experiment;
+pub use heuristic::HeuristicScorer;
+pub use logistic::LogisticScorer;
+pub use pipeline::ScoringPipeline;
+pub use replay::ReplayIndex;
+pub use session::SessionStore;
+pub use shadow::Shadow;
+pub use trees::TreeEnsembleScorer;
+
+/// Names accepted by `scoring.engine`
+pub const ENGINES: &[&str] = &[
+    HeuristicScorer::NAME,
+    LogisticScorer::NAME,
+    TreeEnsembleScorer::NAME,
+    AnomalyScorer::NAME,
+    EnsembleScorer::NAME,
+];
+
+#[derive(Error, Debug)]
+pub enum ScoringError {
+    #[error("Unknown scoring engine: {0}")]
+    UnknownEngine(String),
+    
+    #[error("Scoring engine '{0}' needs scoring.model_path")]
+    MissingModel(String),
+    
+    #[error("Failed to read model file: {0}")]
+    Io(#[from] std::io::Error),
+    
+    #[error("Failed to parse model file: {0}")]
+    Parse(String),
+    
+    #[error("Model references unknown feature: {0}")]
+    UnknownFeature(String),
+    
+    #[error("Invalid model: {0}")]
+    InvalidModel(String),
+}
+
+/// A scoring engine that turns typing features into a human-likeness score
+pub trait Scorer: Send + Sync {
+    /// Engine name as used in `scoring.engine`
+    fn name(&self) -> &'static str;
+
+    /// Score between 0.0 (bot) and 1.0 (human)
+    fn score(&self, features: &TypingFeatures) -> f64;
+
+    /// Version of the loaded model, for model-based engines
+    fn version(&self) -> Option<&str> {
+        None
+    }
+
+    /// Per-feature contributions, for engines that have them
+    fn breakdown(&self, _features: &TypingFeatures) -> Vec<FeatureScore> {
+        Vec::new()
+    }
+
+    /// Output of each member, for engines combining several scorers
+    fn members(&self, _features: &TypingFeatures) -> Vec<MemberScore> {
+        Vec::new()
+    }
+
+    /// Score along with the member outputs it was combined from, running each member once
+    fn score_with_members(&self, features: &TypingFeatures) -> (f64, Vec<MemberScore>) {
+        (self.score(features), self.members(features))
+    }
+}
+
+/// Check whether an engine name is known
+pub fn is_known_engine(engine: &str) -> bool {
+    ENGINES.contains(&engine)
+}
+
+/// Build the scorer selected in the scoring config
+pub fn build_scorer(config: &ScoringConfig) -> Result<Arc<dyn Scorer>, ScoringError> {
+    match config.engine.as_str() {
+        HeuristicScorer::NAME => Ok(Arc::new(HeuristicScorer::new(
+            config.feature_weights.clone(),
+            config.normalization.clone(),
+        ))),
+        LogisticScorer::NAME => Ok(Arc::new(LogisticScorer::from_file(model_path(config)?)?)),
+        TreeEnsembleScorer::NAME => Ok(Arc::new(TreeEnsembleScorer::from_file(model_path(config)?)?)),
+        AnomalyScorer::NAME => Ok(Arc::new(AnomalyScorer::from_file(model_path(config)?)?)),
+        EnsembleScorer::NAME => Ok(Arc::new(EnsembleScorer::new(config)?)),
+        other => Err(ScoringError::UnknownEngine(other.to_string())),
+    }
+}
+
+fn model_path(config: &ScoringConfig) -> Result<&str, ScoringError> {
+    config
+        .model_path
+        .as_deref()
+        .ok_or_else(|| ScoringError::MissingModel(config.engine.clone()))
+}
+
+/// Read a JSON or TOML model file, picking the format from the extension
+pub fn load_model_file<T: DeserializeOwned>(path: &str) -> Result<T, ScoringError> {
+    let contents = std::fs::read_to_string(path)?;
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&contents).map_err(|e| ScoringError::Parse(e.to_string())),
+        Some("toml") => toml::from_str(&contents).map_err(|e| ScoringError::Parse(e.to_string())),
+        _ => Err(ScoringError::Parse(format!("{} is neither .json nor .toml", path))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_default_scorer() {
+        let scorer = build_scorer(&ScoringConfig::default()).unwrap();
+        assert_eq!(scorer.name(), "heuristic");
+    }
+
+    #[test]
+    fn test_build_unknown_scorer() {
+        let config = ScoringConfig {
+            engine: "magic".to_string(),
+            ..ScoringConfig::default()
+        };
+        assert!(matches!(build_scorer(&config), Err(ScoringError::UnknownEngine(_))));
+    }
+
+    #[test]
+    fn test_model_engine_needs_path() {
+        let config = ScoringConfig {
+            engine: LogisticScorer::NAME.to_string(),
+            ..ScoringConfig::default()
+        };
+        assert!(matches!(build_scorer(&config), Err(ScoringError::MissingModel(_))));
+    }
+}
@@ -0,0 +1,436 @@
+use std::sync::Arc;
+
+use crate::{
+    config::{ConsistencyConfig, ScoringConfig},
+    models::{
+        features::NormalizationCurves,
+        payload::{TypingFeaturePayload, TypingMeta},
+        response::{Confidence, DecisionBand, ScoreResponse, Thresholds},
+    },
+    scoring::{
+        self,
+        rules::{Rule, RuleContext, RuleEffect, RuleStage},
+        AnomalyScorer, Calibration, HeuristicScorer, Scorer, ScoringError,
+    },
+};
+
+/// Name of the fallback profile built from the top-level scoring settings
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Scorer and settings used for sessions matching a profile
+pub struct Profile {
+    pub name: String,
+    platforms: Vec<String>,
+    locales: Vec<String>,
+    pub thresholds: Thresholds,
+    pub normalization: NormalizationCurves,
+    pub scorer: Arc<dyn Scorer>,
+}
+
+impl Profile {
+    fn matches(&self, meta: &TypingMeta) -> bool {
+        let any_match = |patterns: &[String], value: &str| {
+            patterns.is_empty() || patterns.iter().any(|pattern| matches_pattern(pattern, value))
+        };
+        any_match(&self.platforms, &meta.platform) && any_match(&self.locales, &meta.locale)
+    }
+}
+
+/// Turns a validated payload into a score response
+pub struct ScoringPipeline {
+    profiles: Vec<Profile>,
+    default: Profile,
+    calibration: Option<Calibration>,
+    /// Population scorer and its share of the final score
+    anomaly: Option<(AnomalyScorer, f64)>,
+    min_events: u32,
+    verbose_members: bool,
+    consistency: ConsistencyConfig,
+    rules: Vec<Rule>,
+}
+
+impl ScoringPipeline {
+    /// Build the scorers for the fallback and every configured profile
+    pub fn new(config: &ScoringConfig) -> Result<Self, ScoringError> {
+        let scorer = scoring::build_scorer(config)?;
+
+        let profiles = config
+            .profiles
+            .iter()
+            .map(|profile| {
+                let normalization = profile
+                    .normalization
+                    .clone()
+                    .unwrap_or_else(|| config.normalization.clone());
+                // Weights and curves only matter to the heuristic, model engines are shared
+                let profile_scorer: Arc<dyn Scorer> = if scorer.name() == HeuristicScorer::NAME {
+                    let weights = profile
+                        .feature_weights
+                        .clone()
+                        .unwrap_or_else(|| config.feature_weights.clone());
+                    Arc::new(HeuristicScorer::new(weights, normalization.clone()))
+                } else {
+                    scorer.clone()
+                };
+
+                let bands = match &profile.bands {
+                    Some(bands) => bands.clone(),
+                    None => default_bands(
+                        config,
+                        profile.suspicious_threshold.unwrap_or(config.suspicious_threshold),
+                    ),
+                };
+
+                Profile {
+                    name: profile.name.clone(),
+                    platforms: profile.platforms.clone(),
+                    locales: profile.locales.clone(),
+                    thresholds: Thresholds::from_bands(bands),
+                    normalization,
+                    scorer: profile_scorer,
+                }
+            })
+            .collect();
+
+        let default = Profile {
+            name: DEFAULT_PROFILE.to_string(),
+            platforms: Vec::new(),
+            locales: Vec::new(),
+            thresholds: Thresholds::from_bands(default_bands(config, config.suspicious_threshold)),
+            normalization: config.normalization.clone(),
+            scorer,
+        };
+
+        let calibration = config
+            .calibration_path
+            .as_deref()
+            .map(Calibration::from_file)
+            .transpose()?;
+
+        let anomaly = config
+            .anomaly
+            .as_ref()
+            .map(|anomaly| AnomalyScorer::from_file(&anomaly.population_path).map(|scorer| (scorer, anomaly.weight)))
+            .transpose()?;
+
+        Ok(Self {
+            profiles,
+            default,
+            calibration,
+            anomaly,
+            min_events: config.min_events,
+            verbose_members: config.ensemble.as_ref().is_some_and(|ensemble| ensemble.verbose),
+            consistency: config.consistency.clone(),
+            rules: config.rules.clone(),
+        })
+    }
+
+    /// The fallback profile
+    pub fn default_profile(&self) -> &Profile {
+        &self.default
+    }
+
+    /// Calibration applied after the scorer, if configured
+    pub fn calibration(&self) -> Option<&Calibration> {
+        self.calibration.as_ref()
+    }
+
+    /// Uncalibrated score of a payload from its profile's scorer
+    pub fn raw_score(&self, payload: &TypingFeaturePayload) -> f64 {
+        self.select_profile(payload.meta.as_ref()).scorer.score(&payload.features)
+    }
+
+    /// First profile matching the session metadata, or the fallback
+    pub fn select_profile(&self, meta: Option<&TypingMeta>) -> &Profile {
+        meta.and_then(|meta| self.profiles.iter().find(|profile| profile.matches(meta)))
+            .unwrap_or(&self.default)
+    }
+
+    /// Score a payload with its profile, then apply the hard rules
+    pub fn evaluate(&self, payload: &TypingFeaturePayload) -> ScoreResponse {
+        let features = &payload.features;
+        let profile = self.select_profile(payload.meta.as_ref());
+        let mut context = RuleContext {
+            features,
+            meta: payload.meta.as_ref(),
+            score: None,
+        };
+        let mut matched = self.matching_rules(RuleStage::Pre, &context);
+
+        // Members run once, their outputs kept only when they are returned
+        let (raw_score, members) = if self.verbose_members {
+            profile.scorer.score_with_members(features)
+        } else {
+            (profile.scorer.score(features), Vec::new())
+        };
+        let calibrated = match &self.calibration {
+            Some(calibration) => calibration.apply(raw_score),
+            None => raw_score,
+        };
+        let anomaly = self.anomaly.as_ref().map(|(scorer, _)| scorer.report(features));
+        let blended = match (&self.anomaly, anomaly) {
+            (Some((_, weight)), Some(report)) => (1.0 - weight) * calibrated + weight * report.score,
+            _ => calibrated,
+        };
+        context.score = Some(cap_score(blended, &matched));
+        matched.extend(self.matching_rules(RuleStage::Post, &context));
+        let score = cap_score(blended, &matched);
+
+        let mut response = ScoreResponse::with_thresholds(score, profile.thresholds.clone());
+        if self.calibration.is_some() {
+            response.raw_score = Some(raw_score);
+        }
+        response.anomaly = anomaly;
+        response
+            .hints
+            .extend(ScoreResponse::feature_hints(features, &features.sub_scores(&profile.normalization)));
+        response.breakdown = profile.scorer.breakdown(features);
+        response.members = members;
+        response.profile = profile.name.clone();
+        response.confidence = Some(Confidence::estimate(score, features));
+        if features.events < self.min_events {
+            response.mark_insufficient_data();
+        }
+        if self.consistency.enabled {
+            let failed = payload.inconsistencies(self.consistency.max_zero_interval_share);
+            if !failed.is_empty() {
+                response.mark_inconsistent(&failed, self.consistency.force_suspicious);
+            }
+        }
+
+        // The first forced label wins, pre rules before post rules
+        let mut forced = false;
+        for rule in &matched {
+            match &rule.effect {
+                RuleEffect::ForceLabel { label, action } if !forced => {
+                    response.label = label.clone();
+                    response.action = *action;
+                    forced = true;
+                }
+                RuleEffect::AddHint { hint } => response.hints.push(hint.clone()),
+                _ => {}
+            }
+        }
+        response.matched_rules = matched.iter().map(|rule| rule.id.clone()).collect();
+        response
+    }
+
+    fn matching_rules(&self, stage: RuleStage, context: &RuleContext) -> Vec<&Rule> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.stage == stage && rule.matches(context))
+            .collect()
+    }
+}
+
+/// Score lowered to the tightest cap among the matched rules
+fn cap_score(score: f64, rules: &[&Rule]) -> f64 {
+    rules.iter().fold(score, |score, rule| match rule.effect {
+        RuleEffect::CapScore { max } => score.min(max),
+        _ => score,
+    })
+}
+
+/// Configured bands, or the two implied by a threshold when none are configured
+fn default_bands(config: &ScoringConfig, suspicious_threshold: f64) -> Vec<DecisionBand> {
+    if config.bands.is_empty() {
+        DecisionBand::from_threshold(suspicious_threshold)
+    } else {
+        config.bands.clone()
+    }
+}
+
+/// Case-insensitive glob match with `*` wildcards, treating `-` and `_` alike
+pub(crate) fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let normalize = |s: &str| s.to_lowercase().replace('-', "_");
+    let (pattern, value) = (normalize(pattern), normalize(value));
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !value.starts_with(first) || value.len() < first.len() + last.len() || !value.ends_with(last) {
+        return false;
+    }
+
+    // Middle parts must appear in order between the prefix and suffix
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AnomalyConfig, ProfileConfig};
+
+    fn payload(events: u32) -> TypingFeaturePayload {
+        TypingFeaturePayload::new(
+            crate::models::features::TypingFeatures {
+                events,
+                iki_mean: 20.0,
+                iki_std: 1.0,
+                iki_iqr: 2.0,
+                burstiness: -0.9,
+                entropy: 0.5,
+                backspace_per_100: 0.0,
+                paste_events: 0,
+                jitter_mad: 0.5,
+                outlier_ratio: 0.0,
+            },
+            None,
+        )
+    }
+
+    fn meta(platform: &str, locale: &str) -> TypingMeta {
+        TypingMeta {
+            window_ms: 5000,
+            locale: locale.to_string(),
+            platform: platform.to_string(),
+            app_ver: "1.0.0".to_string(),
+        }
+    }
+
+    fn profile(name: &str, platforms: &[&str], locales: &[&str]) -> ProfileConfig {
+        ProfileConfig {
+            name: name.to_string(),
+            platforms: platforms.iter().map(|p| p.to_string()).collect(),
+            locales: locales.iter().map(|l| l.to_string()).collect(),
+            suspicious_threshold: Some(0.3),
+            bands: None,
+            feature_weights: None,
+            normalization: None,
+        }
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        assert!(matches_pattern("android", "Android"));
+        assert!(matches_pattern("zh*", "zh_CN"));
+        assert!(matches_pattern("*_JP", "ja-JP"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("a*b*c", "aXbYc"));
+        assert!(!matches_pattern("ios", "android"));
+        assert!(!matches_pattern("a*b*c", "acb"));
+    }
+
+    #[test]
+    fn test_profile_selection() {
+        let config = ScoringConfig {
+            profiles: vec![
+                profile("cjk_ime", &[], &["zh*", "ja*", "ko*"]),
+                profile("touch", &["android", "ios"], &[]),
+            ],
+            ..ScoringConfig::default()
+        };
+        let pipeline = ScoringPipeline::new(&config).unwrap();
+
+        assert_eq!(pipeline.select_profile(Some(&meta("ios", "ja_JP"))).name, "cjk_ime");
+        assert_eq!(pipeline.select_profile(Some(&meta("android", "en_US"))).name, "touch");
+        assert_eq!(pipeline.select_profile(Some(&meta("web", "en_US"))).name, DEFAULT_PROFILE);
+        assert_eq!(pipeline.select_profile(None).name, DEFAULT_PROFILE);
+        assert_eq!(pipeline.select_profile(Some(&meta("ios", "de_DE"))).thresholds.suspicious_below, 0.3);
+    }
+
+    #[test]
+    fn test_short_window_is_insufficient_data() {
+        let pipeline = ScoringPipeline::new(&ScoringConfig::default()).unwrap();
+
+        let short = pipeline.evaluate(&payload(8));
+        assert_eq!(short.label, "insufficient_data");
+        assert!(short.hints.contains(&"too_few_events".to_string()));
+        assert!(!short.hints.contains(&"suspicious_patterns".to_string()));
+
+        let long = pipeline.evaluate(&payload(200));
+        assert_eq!(long.label, "suspicious");
+        assert!(long.confidence.unwrap().level > short.confidence.unwrap().level);
+    }
+
+    #[test]
+    fn test_rules_override_score() {
+        #[derive(serde::Deserialize)]
+        struct RuleList {
+            rules: Vec<Rule>,
+        }
+        let list: RuleList = toml::from_str(
+            r#"
+            [[rules]]
+            id = "too_fast"
+            conditions = [{ field = "iki_mean", op = "lt", value = 30.0 }]
+            effect = { type = "cap_score", max = 0.05 }
+
+            [[rules]]
+            id = "zero_jitter"
+            stage = "post"
+            conditions = [{ field = "score", op = "le", value = 0.05 }]
+            effect = { type = "force_label", label = "bot", action = "block" }
+
+            [[rules]]
+            id = "never"
+            conditions = [{ field = "paste_events", op = "gt", value = 100.0 }]
+            effect = { type = "add_hint", hint = "unused" }
+            "#,
+        )
+        .unwrap();
+        let config = ScoringConfig {
+            rules: list.rules,
+            ..ScoringConfig::default()
+        };
+        let pipeline = ScoringPipeline::new(&config).unwrap();
+
+        let response = pipeline.evaluate(&payload(8));
+        assert!(response.score <= 0.05);
+        assert_eq!(response.label, "bot");
+        assert_eq!(response.action, crate::models::response::Action::Block);
+        assert_eq!(response.matched_rules, vec!["too_fast", "zero_jitter"]);
+        assert!(!response.hints.contains(&"unused".to_string()));
+    }
+
+    #[test]
+    fn test_inconsistent_features_forced_suspicious() {
+        let config = ScoringConfig {
+            consistency: ConsistencyConfig {
+                enabled: true,
+                force_suspicious: true,
+                ..ConsistencyConfig::default()
+            },
+            ..ScoringConfig::default()
+        };
+        let pipeline = ScoringPipeline::new(&config).unwrap();
+
+        let mut forged = payload(40);
+        forged.features.iki_mean = 150.0;
+        forged.features.iki_std = 50.0;
+        forged.features.burstiness = 0.4;
+        let response = pipeline.evaluate(&forged);
+
+        assert_eq!(response.label, "suspicious");
+        assert!(response.hints.contains(&"inconsistent_features".to_string()));
+        assert_eq!(response.inconsistencies, vec!["burstiness_mismatch"]);
+    }
+
+    #[test]
+    fn test_anomaly_blended_into_score() {
+        let population = concat!(env!("CARGO_MANIFEST_DIR"), "/config/models/population-example.json");
+        let config = |weight| ScoringConfig {
+            anomaly: Some(AnomalyConfig {
+                population_path: population.to_string(),
+                weight,
+            }),
+            ..ScoringConfig::default()
+        };
+        let reported = ScoringPipeline::new(&config(0.0)).unwrap().evaluate(&payload(200));
+        let blended = ScoringPipeline::new(&config(0.5)).unwrap().evaluate(&payload(200));
+
+        let anomaly = reported.anomaly.unwrap();
+        assert!(anomaly.distance > 4.0);
+        assert!((blended.score - (0.5 * reported.score + 0.5 * anomaly.score)).abs() < 1e-9);
+    }
+}
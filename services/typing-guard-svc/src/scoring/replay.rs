@@ -0,0 +1,217 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{config::ReplayConfig, models::features::TypingFeatures};
+
+/// Distance to a rounding boundary, in rounding steps, within which the cell across it is checked too
+const NEIGHBOUR_MARGIN: f64 = 0.25;
+
+/// A value rounded to significant digits, as mantissa and decimal exponent
+type Cell = (i64, i32);
+
+/// First session seen with a quantized feature vector
+struct Sighting {
+    session_id: Uuid,
+    last_seen: Instant,
+}
+
+#[derive(Default)]
+struct Index {
+    sightings: HashMap<u64, Sighting>,
+    /// Every sighting in time order, to expire the index front to back; bounds the index
+    order: VecDeque<(Instant, u64)>,
+}
+
+/// Recently seen feature vectors, to catch one session's features replayed by another
+pub struct ReplayIndex {
+    window: Duration,
+    max_entries: usize,
+    significant_digits: u32,
+    pub penalty: f64,
+    index: Mutex<Index>,
+}
+
+impl ReplayIndex {
+    pub fn new(config: &ReplayConfig) -> Self {
+        Self {
+            window: Duration::from_secs(config.window_secs),
+            max_entries: config.max_entries,
+            significant_digits: config.significant_digits,
+            penalty: config.penalty,
+            index: Mutex::new(Index::default()),
+        }
+    }
+
+    /// Index a submission and tell whether another session sent the same features recently
+    pub fn check(&self, session_id: Uuid, features: &TypingFeatures) -> bool {
+        self.check_at(Instant::now(), session_id, features)
+    }
+
+    fn check_at(&self, now: Instant, session_id: Uuid, features: &TypingFeatures) -> bool {
+        let keys = self.keys(features);
+        let key = keys[0];
+        let mut index = self.index.lock().unwrap();
+        self.expire(&mut index, now);
+
+        let replayed = keys
+            .iter()
+            .any(|key| index.sightings.get(key).is_some_and(|sighting| sighting.session_id != session_id));
+        match index.sightings.get_mut(&key) {
+            Some(sighting) => sighting.last_seen = now,
+            None => {
+                index.sightings.insert(
+                    key,
+                    Sighting {
+                        session_id,
+                        last_seen: now,
+                    },
+                );
+            }
+        }
+        index.order.push_back((now, key));
+
+        if replayed {
+            warn!("Session {} replayed features seen in another session", session_id);
+        }
+        replayed
+    }
+
+    /// Drop sightings older than the window, then the oldest beyond capacity
+    fn expire(&self, index: &mut Index, now: Instant) {
+        while let Some(&(seen, key)) = index.order.front() {
+            if now.duration_since(seen) <= self.window && index.order.len() < self.max_entries {
+                break;
+            }
+            index.order.pop_front();
+            // A later sighting of the same key keeps it indexed
+            if index.sightings.get(&key).is_some_and(|sighting| sighting.last_seen <= seen) {
+                index.sightings.remove(&key);
+            }
+        }
+    }
+
+    /// Hashes of the cells the features could be a jittered copy of, their own first
+    ///
+    /// Features are rounded to the configured significant digits. A feature within
+    /// `NEIGHBOUR_MARGIN` of a step from a rounding boundary also tries the cell across it,
+    /// so two vectors closer than that in every feature always share a key.
+    fn keys(&self, features: &TypingFeatures) -> Vec<u64> {
+        let cells = features.values().map(|value| nearby_cells(value, self.significant_digits));
+        let borderline: Vec<usize> = (0..cells.len()).filter(|&i| cells[i].1.is_some()).collect();
+
+        (0..1u32 << borderline.len())
+            .map(|crossed| {
+                let mut hasher = DefaultHasher::new();
+                for (i, &(own, across)) in cells.iter().enumerate() {
+                    let bit = borderline.iter().position(|&feature| feature == i);
+                    match (bit, across) {
+                        (Some(bit), Some(across)) if crossed & (1 << bit) != 0 => across.hash(&mut hasher),
+                        _ => own.hash(&mut hasher),
+                    }
+                }
+                hasher.finish()
+            })
+            .collect()
+    }
+}
+
+/// Cell of a value rounded to `digits` significant digits, and the cell across the nearest
+/// rounding boundary when the value lies within `NEIGHBOUR_MARGIN` of it
+fn nearby_cells(value: f64, digits: u32) -> (Cell, Option<Cell>) {
+    if value == 0.0 || !value.is_finite() {
+        return ((0, 0), None);
+    }
+    let exponent = value.abs().log10().floor() as i32 + 1 - digits as i32;
+    let scaled = value / 10f64.powi(exponent);
+    let rounded = scaled.round();
+    let offset = scaled - rounded;
+    let across = (0.5 - offset.abs() < NEIGHBOUR_MARGIN)
+        .then(|| normalize((rounded + offset.signum()) as i64, exponent, digits));
+    (normalize(rounded as i64, exponent, digits), across)
+}
+
+/// 99.6 at two digits rounds to 100, the same cell as 100.4
+fn normalize(mantissa: i64, exponent: i32, digits: u32) -> Cell {
+    if mantissa.unsigned_abs() >= 10u64.pow(digits) {
+        return (mantissa / 10, exponent + 1);
+    }
+    (mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(iki_mean: f64) -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: -0.5,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.05,
+        }
+    }
+
+    fn index(max_entries: usize) -> ReplayIndex {
+        ReplayIndex::new(&ReplayConfig {
+            max_entries,
+            ..ReplayConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_nearby_cells() {
+        assert_eq!(nearby_cells(147.9, 2), ((15, 1), None));
+        assert_eq!(nearby_cells(145.3, 2), ((15, 1), Some((14, 1))));
+        assert_eq!(nearby_cells(144.9, 2), ((14, 1), Some((15, 1))));
+        assert_eq!(nearby_cells(99.6, 2), ((10, 1), Some((99, 0))));
+        assert_eq!(nearby_cells(100.4, 2), ((10, 1), None));
+        assert_eq!(nearby_cells(-0.224, 2), ((-22, -2), Some((-23, -2))));
+    }
+
+    #[test]
+    fn test_near_duplicates_across_sessions_flagged() {
+        let index = index(100);
+        let now = Instant::now();
+        let (original, replayer) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(!index.check_at(now, original, &features(146.0)));
+        // The same session sending its features again is not a replay
+        assert!(!index.check_at(now, original, &features(146.0)));
+        assert!(index.check_at(now, replayer, &features(148.2)));
+        assert!(!index.check_at(now, replayer, &features(180.0)));
+
+        // Either side of a rounding boundary
+        assert!(!index.check_at(now, original, &features(244.9)));
+        assert!(index.check_at(now, replayer, &features(245.1)));
+
+        let later = now + index.window + Duration::from_secs(1);
+        assert!(!index.check_at(later, replayer, &features(146.0)));
+    }
+
+    #[test]
+    fn test_index_is_bounded() {
+        let index = index(3);
+        let now = Instant::now();
+        for i in 0..10 {
+            index.check_at(now, Uuid::new_v4(), &features(100.0 + 20.0 * i as f64));
+        }
+
+        assert!(index.index.lock().unwrap().sightings.len() <= 3);
+        // The first vector was evicted, the last is still indexed
+        assert!(!index.check_at(now, Uuid::new_v4(), &features(100.0)));
+        assert!(index.check_at(now, Uuid::new_v4(), &features(280.0)));
+    }
+}
@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{
+        features::TypingFeatures,
+        payload::TypingMeta,
+        response::Action,
+    },
+    scoring::{pipeline::matches_pattern, Scorer},
+};
+
+/// Meta fields rules can compare against text values
+const META_TEXT_FIELDS: [&str; 3] = ["meta.platform", "meta.locale", "meta.app_ver"];
+
+/// Meta fields rules can compare against numbers
+const META_NUMBER_FIELDS: [&str; 1] = ["meta.window_ms"];
+
+/// Field holding the scorer output, only visible to `post` rules
+const SCORE_FIELD: &str = "score";
+
+/// A hard rule that settles or adjusts the outcome regardless of the weighted score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    /// Identifier reported in `matched_rules`
+    pub id: String,
+
+    /// Whether the rule runs before or after the scorer
+    #[serde(default)]
+    pub stage: RuleStage,
+
+    /// Conditions that must all hold
+    pub conditions: Vec<Condition>,
+
+    /// What happens when the rule matches
+    pub effect: RuleEffect,
+}
+
+/// When a rule is evaluated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleStage {
+    /// Before scoring, on features and metadata only
+    #[default]
+    Pre,
+
+    /// After scoring and calibration, with `score` available
+    Post,
+}
+
+/// Comparison of one field against a value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Condition {
+    /// `TypingFeatures` field, `meta.<field>` or `score`
+    pub field: String,
+
+    pub op: Op,
+
+    pub value: ConditionValue,
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// Glob match on text fields, as used by profile patterns
+    Matches,
+}
+
+/// Number for feature, window and score fields, text for meta strings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConditionValue {
+    Number(f64),
+    Text(String),
+}
+
+/// Outcome of a matching rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleEffect {
+    /// Replace the label and action, overriding bands and `insufficient_data`
+    ForceLabel { label: String, action: Action },
+
+    /// Lower the score to at most `max` before bands are applied
+    CapScore { max: f64 },
+
+    /// Add a hint to the response
+    AddHint { hint: String },
+}
+
+/// Values a rule can look at
+pub struct RuleContext<'a> {
+    pub features: &'a TypingFeatures,
+    pub meta: Option<&'a TypingMeta>,
+    /// Set once the scorer has run
+    pub score: Option<f64>,
+}
+
+impl RuleContext<'_> {
+    fn number(&self, field: &str) -> Option<f64> {
+        match field {
+            SCORE_FIELD => self.score,
+            "meta.window_ms" => self.meta.map(|meta| f64::from(meta.window_ms)),
+            _ => TypingFeatures::field_index(field).map(|index| self.features.values()[index]),
+        }
+    }
+
+    fn text(&self, field: &str) -> Option<&str> {
+        let meta = self.meta?;
+        match field {
+            "meta.platform" => Some(&meta.platform),
+            "meta.locale" => Some(&meta.locale),
+            "meta.app_ver" => Some(&meta.app_ver),
+            _ => None,
+        }
+    }
+}
+
+impl Condition {
+    /// Whether the condition holds; fields that aren't available never match
+    pub fn matches(&self, context: &RuleContext) -> bool {
+        match &self.value {
+            ConditionValue::Number(expected) => {
+                let Some(actual) = context.number(&self.field) else {
+                    return false;
+                };
+                match self.op {
+                    Op::Lt => actual < *expected,
+                    Op::Le => actual <= *expected,
+                    Op::Gt => actual > *expected,
+                    Op::Ge => actual >= *expected,
+                    Op::Eq => actual == *expected,
+                    Op::Ne => actual != *expected,
+                    Op::Matches => false,
+                }
+            }
+            ConditionValue::Text(expected) => {
+                let Some(actual) = context.text(&self.field) else {
+                    return false;
+                };
+                match self.op {
+                    Op::Eq => actual.eq_ignore_ascii_case(expected),
+                    Op::Ne => !actual.eq_ignore_ascii_case(expected),
+                    Op::Matches => matches_pattern(expected, actual),
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+impl Rule {
+    /// Whether every condition holds
+    pub fn matches(&self, context: &RuleContext) -> bool {
+        self.conditions.iter().all(|condition| condition.matches(context))
+    }
+
+    /// Check fields, operators and effect parameters
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("Rule id cannot be empty".to_string());
+        }
+        if self.conditions.is_empty() {
+            return Err(format!("Rule '{}' needs at least one condition", self.id));
+        }
+
+        for condition in &self.conditions {
+            let field = condition.field.as_str();
+            let numeric = TypingFeatures::field_index(field).is_some()
+                || META_NUMBER_FIELDS.contains(&field)
+                || field == SCORE_FIELD;
+            let text = META_TEXT_FIELDS.contains(&field);
+
+            if !numeric && !text {
+                return Err(format!("Rule '{}' references unknown field '{}'", self.id, field));
+            }
+            if field == SCORE_FIELD && self.stage == RuleStage::Pre {
+                return Err(format!("Rule '{}' uses 'score' but only post rules can", self.id));
+            }
+            let valid = match &condition.value {
+                ConditionValue::Number(value) => numeric && condition.op != Op::Matches && value.is_finite(),
+                ConditionValue::Text(_) => text && matches!(condition.op, Op::Eq | Op::Ne | Op::Matches),
+            };
+            if !valid {
+                return Err(format!(
+                    "Rule '{}' compares '{}' with {:?} against an unsuitable value",
+                    self.id, field, condition.op
+                ));
+            }
+        }
+
+        match &self.effect {
+            RuleEffect::ForceLabel { label, .. } if label.is_empty() => {
+                Err(format!("Rule '{}' forces an empty label", self.id))
+            }
+            RuleEffect::CapScore { max } if !(0.0..=1.0).contains(max) => {
+                Err(format!("Rule '{}' cap must be between 0.0 and 1.0", self.id))
+            }
+            RuleEffect::AddHint { hint } if hint.is_empty() => Err(format!("Rule '{}' adds an empty hint", self.id)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Ensemble member scoring sessions by the pre-stage rules alone
+pub struct RulesScorer {
+    rules: Vec<Rule>,
+}
+
+impl RulesScorer {
+    pub const NAME: &'static str = "rules";
+
+    pub fn new(rules: &[Rule]) -> Self {
+        Self {
+            rules: rules.iter().filter(|rule| rule.stage == RuleStage::Pre).cloned().collect(),
+        }
+    }
+}
+
+impl Scorer for RulesScorer {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// 1.0 unless a rule matches: its cap, or 0.0 for a forced label that isn't allowed.
+    /// Scorers don't see the metadata, so `meta.*` conditions never match here.
+    fn score(&self, features: &TypingFeatures) -> f64 {
+        let context = RuleContext {
+            features,
+            meta: None,
+            score: None,
+        };
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(&context))
+            .fold(1.0, |score, rule| match &rule.effect {
+                RuleEffect::CapScore { max } => score.min(*max),
+                RuleEffect::ForceLabel { action, .. } if *action != Action::Allow => 0.0,
+                _ => score,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features() -> TypingFeatures {
+        TypingFeatures {
+            events: 12,
+            iki_mean: 25.0,
+            iki_std: 2.0,
+            iki_iqr: 3.0,
+            burstiness: -0.8,
+            entropy: 1.0,
+            backspace_per_100: 0.0,
+            paste_events: 3,
+            jitter_mad: 0.0,
+            outlier_ratio: 0.0,
+        }
+    }
+
+    fn rule(toml: &str) -> Rule {
+        let rule: Rule = toml::from_str(toml).unwrap();
+        rule.validate().unwrap();
+        rule
+    }
+
+    #[test]
+    fn test_conditions_must_all_hold() {
+        let paste_burst = rule(
+            r#"
+            id = "paste_burst"
+            conditions = [
+                { field = "paste_events", op = "gt", value = 2 },
+                { field = "events", op = "lt", value = 30 },
+            ]
+            effect = { type = "force_label", label = "bot", action = "block" }
+            "#,
+        );
+        let features = features();
+        let context = RuleContext {
+            features: &features,
+            meta: None,
+            score: None,
+        };
+        assert!(paste_burst.matches(&context));
+
+        let long = TypingFeatures { events: 80, ..features.clone() };
+        let context = RuleContext {
+            features: &long,
+            meta: None,
+            score: None,
+        };
+        assert!(!paste_burst.matches(&context));
+    }
+
+    #[test]
+    fn test_meta_and_score_conditions() {
+        let web_low = rule(
+            r#"
+            id = "web_low_score"
+            stage = "post"
+            conditions = [
+                { field = "meta.platform", op = "matches", value = "web*" },
+                { field = "score", op = "lt", value = 0.2 },
+            ]
+            effect = { type = "add_hint", hint = "web_automation" }
+            "#,
+        );
+        let features = features();
+        let meta = TypingMeta {
+            window_ms: 5000,
+            locale: "en_US".to_string(),
+            platform: "Web".to_string(),
+            app_ver: "1.0.0".to_string(),
+        };
+
+        let scored = |score| RuleContext {
+            features: &features,
+            meta: Some(&meta),
+            score: Some(score),
+        };
+        assert!(web_low.matches(&scored(0.1)));
+        assert!(!web_low.matches(&scored(0.5)));
+        let without_meta = RuleContext {
+            features: &features,
+            meta: None,
+            score: Some(0.1),
+        };
+        assert!(!web_low.matches(&without_meta));
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let parse = |toml: &str| toml::from_str::<Rule>(toml).unwrap().validate();
+
+        assert!(parse(
+            r#"
+            id = "unknown"
+            conditions = [{ field = "mouse_speed", op = "lt", value = 1.0 }]
+            effect = { type = "cap_score", max = 0.1 }
+            "#
+        )
+        .is_err());
+        assert!(parse(
+            r#"
+            id = "score_before_scoring"
+            conditions = [{ field = "score", op = "lt", value = 0.1 }]
+            effect = { type = "cap_score", max = 0.1 }
+            "#
+        )
+        .is_err());
+        assert!(parse(
+            r#"
+            id = "text_on_number"
+            conditions = [{ field = "iki_mean", op = "eq", value = "fast" }]
+            effect = { type = "add_hint", hint = "x" }
+            "#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::{
+    config::SessionConfig,
+    models::{
+        features::TypingFeatures,
+        response::{ScoreResponse, SessionScore},
+    },
+};
+
+/// One scored window of a session
+struct Window {
+    features: TypingFeatures,
+    score: f64,
+}
+
+struct Session {
+    last_seen: Instant,
+    /// Oldest first
+    windows: VecDeque<Window>,
+}
+
+#[derive(Default)]
+struct Sessions {
+    sessions: HashMap<Uuid, Session>,
+    /// Every recorded window's (time, session) in time order, to expire and evict front to back;
+    /// entries older than their session's `last_seen` are stale and skipped
+    order: VecDeque<(Instant, Uuid)>,
+}
+
+/// Recent windows of every active session, forgotten after a period of silence
+pub struct SessionStore {
+    ttl: Duration,
+    max_windows: usize,
+    smoothing: f64,
+    max_sessions: usize,
+    sessions: Mutex<Sessions>,
+}
+
+impl SessionStore {
+    pub fn new(config: &SessionConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.ttl_secs),
+            max_windows: config.max_windows,
+            smoothing: config.smoothing,
+            max_sessions: config.max_sessions,
+            sessions: Mutex::new(Sessions::default()),
+        }
+    }
+
+    /// Add a scored window to its session and return the session-level score
+    pub fn record(&self, session_id: Uuid, features: &TypingFeatures, response: &ScoreResponse) -> SessionScore {
+        self.record_at(Instant::now(), session_id, features, response)
+    }
+
+    fn record_at(
+        &self,
+        now: Instant,
+        session_id: Uuid,
+        features: &TypingFeatures,
+        response: &ScoreResponse,
+    ) -> SessionScore {
+        let mut store = self.sessions.lock().unwrap();
+        let is_new = !store.sessions.contains_key(&session_id);
+        self.expire(&mut store, now, is_new);
+        store.order.push_back((now, session_id));
+
+        let session = store.sessions.entry(session_id).or_insert_with(|| Session {
+            last_seen: now,
+            windows: VecDeque::new(),
+        });
+        if now.duration_since(session.last_seen) > self.ttl {
+            session.windows.clear();
+        }
+        session.last_seen = now;
+        session.windows.push_back(Window {
+            features: features.clone(),
+            score: response.score,
+        });
+        if session.windows.len() > self.max_windows {
+            session.windows.pop_front();
+        }
+
+        let score = self.smoothed(&session.windows);
+        let (label, action) = response.thresholds.verdict(score);
+        SessionScore {
+            score,
+            windows: session.windows.len(),
+            events: session.windows.iter().map(|window| window.features.events).sum(),
+            label,
+            action,
+        }
+    }
+
+    /// EWMA of the window scores, seeded with the oldest
+    fn smoothed(&self, windows: &VecDeque<Window>) -> f64 {
+        let mut scores = windows.iter().map(|window| window.score);
+        let first = scores.next().unwrap_or(0.0);
+        scores.fold(first, |average, score| self.smoothing * score + (1.0 - self.smoothing) * average)
+    }
+
+    /// Drop expired sessions, and the least recently seen ones while a new session needs room
+    fn expire(&self, store: &mut Sessions, now: Instant, is_new: bool) {
+        while let Some(&(seen, id)) = store.order.front() {
+            let current = store.sessions.get(&id).is_some_and(|session| session.last_seen == seen);
+            let full = is_new && store.sessions.len() >= self.max_sessions;
+            if current && now.duration_since(seen) <= self.ttl && !full {
+                break;
+            }
+            store.order.pop_front();
+            if current {
+                store.sessions.remove(&id);
+            }
+        }
+
+        // Busy sessions leave stale entries behind a live front; drop them once they outnumber the
+        // sessions, which keeps the queue linear in the sessions and the work amortized O(1)
+        if store.order.len() > 2 * store.sessions.len().max(1) {
+            let Sessions { sessions, order } = store;
+            order.retain(|(seen, id)| sessions.get(id).is_some_and(|session| session.last_seen == *seen));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features() -> TypingFeatures {
+        TypingFeatures {
+            events: 40,
+            iki_mean: 150.0,
+            iki_std: 50.0,
+            iki_iqr: 60.0,
+            burstiness: -0.5,
+            entropy: 2.8,
+            backspace_per_100: 5.0,
+            paste_events: 0,
+            jitter_mad: 25.0,
+            outlier_ratio: 0.05,
+        }
+    }
+
+    fn store(max_sessions: usize) -> SessionStore {
+        SessionStore::new(&SessionConfig {
+            max_windows: 3,
+            smoothing: 0.5,
+            max_sessions,
+            ..SessionConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_one_noisy_window_does_not_decide() {
+        let store = store(10);
+        let session = Uuid::new_v4();
+        let now = Instant::now();
+
+        for score in [0.8, 0.8] {
+            store.record_at(now, session, &features(), &ScoreResponse::new(score, 0.4));
+        }
+        let noisy = ScoreResponse::new(0.2, 0.4);
+        let result = store.record_at(now, session, &features(), &noisy);
+
+        assert_eq!(noisy.label, "suspicious");
+        assert_eq!((result.windows, result.events), (3, 120));
+        assert!((result.score - 0.5).abs() < 1e-9);
+        assert_eq!(result.label, "likely_human");
+
+        // Only the newest `max_windows` count: 0.8, 0.2, 0.2
+        let result = store.record_at(now, session, &features(), &noisy);
+        assert_eq!(result.windows, 3);
+        assert!((result.score - 0.35).abs() < 1e-9);
+        assert_eq!(result.label, "suspicious");
+    }
+
+    #[test]
+    fn test_sessions_expire() {
+        let store = store(2);
+        let start = Instant::now();
+        let later = start + store.ttl + Duration::from_secs(1);
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        store.record_at(start, a, &features(), &ScoreResponse::new(0.9, 0.4));
+        let result = store.record_at(later, a, &features(), &ScoreResponse::new(0.1, 0.4));
+        assert_eq!(result.windows, 1);
+        assert_eq!(result.score, 0.1);
+
+        // Full: the least recently seen session makes room
+        store.record_at(later + Duration::from_secs(1), b, &features(), &ScoreResponse::new(0.9, 0.4));
+        store.record_at(later + Duration::from_secs(2), c, &features(), &ScoreResponse::new(0.9, 0.4));
+        let sessions = store.sessions.lock().unwrap();
+        assert_eq!(sessions.sessions.len(), 2);
+        assert!(!sessions.sessions.contains_key(&a));
+    }
+
+    #[test]
+    fn test_busy_session_keeps_queue_bounded() {
+        let store = store(10);
+        let now = Instant::now();
+        let (quiet, busy) = (Uuid::new_v4(), Uuid::new_v4());
+
+        store.record_at(now, quiet, &features(), &ScoreResponse::new(0.9, 0.4));
+        for i in 1..1000 {
+            store.record_at(now + Duration::from_millis(i), busy, &features(), &ScoreResponse::new(0.9, 0.4));
+        }
+
+        let sessions = store.sessions.lock().unwrap();
+        assert_eq!(sessions.sessions.len(), 2);
+        assert!(sessions.order.len() <= 4);
+    }
+}
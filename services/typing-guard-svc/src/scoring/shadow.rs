@@ -0,0 +1,129 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    config::ShadowConfig,
+    models::{payload::TypingFeaturePayload, response::ScoreResponse},
+    scoring::{ScoringError, ScoringPipeline},
+};
+
+/// A candidate pipeline scored next to the live one, with its counters
+pub struct Shadow {
+    name: String,
+    pipeline: ScoringPipeline,
+    evaluated: AtomicU64,
+    disagreements: AtomicU64,
+    /// Disagreements by `live_label -> shadow_label`
+    transitions: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Counters of one shadow, as returned by `/shadow`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowStats {
+    pub name: String,
+    pub evaluated: u64,
+    pub disagreements: u64,
+    pub transitions: BTreeMap<String, u64>,
+}
+
+impl Shadow {
+    /// Build the candidate pipeline
+    pub fn new(config: &ShadowConfig) -> Result<Self, ScoringError> {
+        Ok(Self {
+            name: config.name.clone(),
+            pipeline: ScoringPipeline::new(&config.scoring)?,
+            evaluated: AtomicU64::new(0),
+            disagreements: AtomicU64::new(0),
+            transitions: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Score a payload, log it next to the live result and update the counters
+    pub fn observe(&self, payload: &TypingFeaturePayload, live: &ScoreResponse) -> ScoreResponse {
+        let shadow = self.pipeline.evaluate(payload);
+        self.evaluated.fetch_add(1, Ordering::Relaxed);
+
+        let agrees = shadow.label == live.label;
+        if !agrees {
+            self.disagreements.fetch_add(1, Ordering::Relaxed);
+            let transition = format!("{} -> {}", live.label, shadow.label);
+            *self.transitions.lock().unwrap().entry(transition).or_insert(0) += 1;
+        }
+
+        info!(
+            "Shadow {} for session {}: score={:.3}, label={} (live score={:.3}, label={}, agrees={})",
+            self.name, payload.session_id, shadow.score, shadow.label, live.score, live.label, agrees
+        );
+
+        shadow
+    }
+
+    /// Snapshot of the counters
+    pub fn stats(&self) -> ShadowStats {
+        ShadowStats {
+            name: self.name.clone(),
+            evaluated: self.evaluated.load(Ordering::Relaxed),
+            disagreements: self.disagreements.load(Ordering::Relaxed),
+            transitions: self.transitions.lock().unwrap().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ScoringConfig, models::features::TypingFeatures};
+
+    fn payload(iki_mean: f64, iki_std: f64, entropy: f64, jitter_mad: f64) -> TypingFeaturePayload {
+        TypingFeaturePayload::new(
+            TypingFeatures {
+                events: 40,
+                iki_mean,
+                iki_std,
+                iki_iqr: iki_std,
+                burstiness: (iki_std - iki_mean) / (iki_std + iki_mean),
+                entropy,
+                backspace_per_100: 5.0,
+                paste_events: 0,
+                jitter_mad,
+                outlier_ratio: 0.05,
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_disagreements_counted() {
+        let live = ScoringPipeline::new(&ScoringConfig::default()).unwrap();
+        let strict = Shadow::new(&ShadowConfig {
+            name: "strict".to_string(),
+            scoring: ScoringConfig {
+                suspicious_threshold: 0.99,
+                ..ScoringConfig::default()
+            },
+        })
+        .unwrap();
+
+        let sessions = [
+            payload(150.0, 50.0, 2.8, 25.0),
+            payload(160.0, 60.0, 2.6, 30.0),
+            payload(20.0, 1.0, 0.5, 0.5),
+        ];
+        for payload in &sessions {
+            strict.observe(payload, &live.evaluate(payload));
+        }
+
+        let stats = strict.stats();
+        assert_eq!(stats.evaluated, 3);
+        assert_eq!(stats.disagreements, 2);
+        assert_eq!(stats.transitions["likely_human -> suspicious"], 2);
+    }
+}
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::features::TypingFeatures,
+    scoring::{Scorer, ScoringError},
+};
+
+/// Gradient-boosted tree ensemble as stored in a model file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeEnsembleModel {
+    /// Model version, reported by `/config`
+    pub version: String,
+
+    /// `binary:logistic` (sigmoid of the margin) or `reg:squarederror` (raw sum)
+    #[serde(default = "default_objective")]
+    pub objective: String,
+
+    /// Starting prediction, a probability for `binary:logistic` like XGBoost's `base_score`
+    #[serde(default = "default_base_score")]
+    pub base_score: f64,
+
+    /// Trees in XGBoost `dump_format="json"` layout
+    pub trees: Vec<DumpNode>,
+}
+
+fn default_objective() -> String {
+    "binary:logistic".to_string()
+}
+
+fn default_base_score() -> f64 {
+    0.5
+}
+
+/// A node of an XGBoost JSON tree dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpNode {
+    pub nodeid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_condition: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DumpNode>,
+}
+
+/// Flattened node, children addressed by position in the tree's node list
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    Split {
+        feature: usize,
+        threshold: f64,
+        yes: usize,
+        no: usize,
+    },
+    Leaf(f64),
+}
+
+/// Scores sessions with a tree ensemble evaluated natively on `TypingFeatures`
+pub struct TreeEnsembleScorer {
+    version: String,
+    logistic: bool,
+    base_margin: f64,
+    trees: Vec<Vec<Node>>,
+}
+
+impl TreeEnsembleScorer {
+    pub const NAME: &'static str = "tree_ensemble";
+
+    /// Check the model and flatten its trees
+    pub fn new(model: TreeEnsembleModel) -> Result<Self, ScoringError> {
+        let logistic = match model.objective.as_str() {
+            "binary:logistic" => true,
+            "reg:squarederror" => false,
+            other => {
+                return Err(ScoringError::InvalidModel(format!("unsupported objective '{}'", other)));
+            }
+        };
+
+        let base_margin = if logistic {
+            if model.base_score <= 0.0 || model.base_score >= 1.0 {
+                return Err(ScoringError::InvalidModel(
+                    "base_score must be between 0.0 and 1.0 for binary:logistic".to_string(),
+                ));
+            }
+            (model.base_score / (1.0 - model.base_score)).ln()
+        } else {
+            model.base_score
+        };
+
+        let trees = model
+            .trees
+            .iter()
+            .map(|root| {
+                let mut nodes = Vec::new();
+                flatten(root, &mut nodes)?;
+                Ok(nodes)
+            })
+            .collect::<Result<Vec<_>, ScoringError>>()?;
+
+        Ok(Self {
+            version: model.version,
+            logistic,
+            base_margin,
+            trees,
+        })
+    }
+
+    /// Load a model from a JSON or TOML file
+    pub fn from_file(path: &str) -> Result<Self, ScoringError> {
+        Self::new(super::load_model_file(path)?)
+    }
+
+    /// Sum of the base margin and every tree's leaf, before the link function
+    pub fn margin(&self, features: &TypingFeatures) -> f64 {
+        let values = features.values();
+        self.trees
+            .iter()
+            .map(|nodes| predict_tree(nodes, &values))
+            .fold(self.base_margin, |margin, leaf| margin + leaf)
+    }
+}
+
+impl Scorer for TreeEnsembleScorer {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(&self.version)
+    }
+
+    fn score(&self, features: &TypingFeatures) -> f64 {
+        let margin = self.margin(features);
+        if self.logistic {
+            1.0 / (1.0 + (-margin).exp())
+        } else {
+            margin.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Append a dump node and its subtree to `nodes`, returning its position
+fn flatten(node: &DumpNode, nodes: &mut Vec<Node>) -> Result<usize, ScoringError> {
+    let position = nodes.len();
+
+    if let Some(leaf) = node.leaf {
+        if !leaf.is_finite() {
+            return Err(ScoringError::InvalidModel(format!("leaf {} is not finite", node.nodeid)));
+        }
+        nodes.push(Node::Leaf(leaf));
+        return Ok(position);
+    }
+
+    let invalid = |reason: &str| ScoringError::InvalidModel(format!("node {} {}", node.nodeid, reason));
+    let split = node.split.as_deref().ok_or_else(|| invalid("has neither leaf nor split"))?;
+    let feature =
+        TypingFeatures::field_index(split).ok_or_else(|| ScoringError::UnknownFeature(split.to_string()))?;
+    let threshold = node
+        .split_condition
+        .filter(|threshold| threshold.is_finite())
+        .ok_or_else(|| invalid("needs a finite split_condition"))?;
+    let child = |id: Option<u32>| {
+        id.and_then(|id| node.children.iter().find(|child| child.nodeid == id))
+            .ok_or_else(|| invalid("references a missing child"))
+    };
+    let (yes_child, no_child) = (child(node.yes)?, child(node.no)?);
+
+    // Reserve this node's slot, then fill it in once both children are placed
+    nodes.push(Node::Leaf(0.0));
+    let yes = flatten(yes_child, nodes)?;
+    let no = flatten(no_child, nodes)?;
+    nodes[position] = Node::Split {
+        feature,
+        threshold,
+        yes,
+        no,
+    };
+
+    Ok(position)
+}
+
+/// Walk one flattened tree; XGBoost goes to `yes` when value < split_condition
+fn predict_tree(nodes: &[Node], values: &[f64]) -> f64 {
+    let mut position = 0;
+    loop {
+        match nodes[position] {
+            Node::Leaf(value) => return value,
+            Node::Split {
+                feature,
+                threshold,
+                yes,
+                no,
+            } => {
+                position = if values[feature] < threshold { yes } else { no };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct GoldenCase {
+        features: TypingFeatures,
+        margin: f64,
+        prediction: f64,
+    }
+
+    fn golden_model() -> TreeEnsembleScorer {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tree-ensemble-golden.json");
+        TreeEnsembleScorer::from_file(path).unwrap()
+    }
+
+    #[test]
+    fn test_golden_predictions() {
+        let scorer = golden_model();
+        let cases: Vec<GoldenCase> =
+            serde_json::from_str(include_str!("../../tests/fixtures/tree-ensemble-golden-predictions.json")).unwrap();
+
+        // XGBoost predicts in single precision
+        assert!(!cases.is_empty());
+        for case in cases {
+            assert!((scorer.margin(&case.features) - case.margin).abs() < 1e-5);
+            assert!((scorer.score(&case.features) - case.prediction).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_unknown_split_feature_rejected() {
+        let model: TreeEnsembleModel = serde_json::from_str(
+            r#"{
+                "version": "bad",
+                "trees": [{
+                    "nodeid": 0, "split": "mouse_speed", "split_condition": 1.0, "yes": 1, "no": 2,
+                    "children": [{ "nodeid": 1, "leaf": 0.1 }, { "nodeid": 2, "leaf": -0.1 }]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(TreeEnsembleScorer::new(model), Err(ScoringError::UnknownFeature(_))));
+    }
+
+    #[test]
+    fn test_missing_child_rejected() {
+        let model: TreeEnsembleModel = serde_json::from_str(
+            r#"{
+                "version": "bad",
+                "trees": [{
+                    "nodeid": 0, "split": "iki_mean", "split_condition": 1.0, "yes": 1, "no": 5,
+                    "children": [{ "nodeid": 1, "leaf": 0.1 }, { "nodeid": 2, "leaf": -0.1 }]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(TreeEnsembleScorer::new(model), Err(ScoringError::InvalidModel(_))));
+    }
+}
@@ -0,0 +1,306 @@
+use rand::Rng;
+use rand_distr::{Distribution, Exp, LogNormal, Normal};
+
+use crate::{
+    extraction,
+    models::{
+        dataset::{Label, LabeledPayload},
+        payload::{KeyEvent, KeyEventKind, TypingFeaturePayload, TypingMeta},
+    },
+};
+
+/// Fastest interval a human finger produces, in milliseconds
+const MIN_HUMAN_INTERVAL_MS: f64 = 30.0;
+
+/// Distribution of a simulated human's inter-key intervals
+#[derive(Debug, Clone)]
+pub enum IntervalModel {
+    /// Log-normal intervals around a median
+    LogNormal { median_ms: f64, sigma: f64 },
+
+    /// Gaussian motor time plus an exponential planning delay
+    ExGaussian { mu_ms: f64, sigma_ms: f64, tau_ms: f64 },
+}
+
+impl IntervalModel {
+    fn sample(&self, rng: &mut impl Rng) -> f64 {
+        let interval = match *self {
+            Self::LogNormal { median_ms, sigma } => LogNormal::new(median_ms.ln(), sigma).unwrap().sample(rng),
+            Self::ExGaussian { mu_ms, sigma_ms, tau_ms } => {
+                Normal::new(mu_ms, sigma_ms).unwrap().sample(rng) + Exp::new(1.0 / tau_ms).unwrap().sample(rng)
+            }
+        };
+        interval.max(MIN_HUMAN_INTERVAL_MS)
+    }
+}
+
+/// Typing habits of a simulated human
+#[derive(Debug, Clone)]
+pub struct HumanProfile {
+    pub intervals: IntervalModel,
+
+    /// Mean characters per word, spaces excluded
+    pub word_length: f64,
+
+    /// Mean extra pause before each space, in milliseconds
+    pub word_pause_ms: f64,
+
+    /// Chance per character of a typo, typed and then corrected with backspaces
+    pub error_rate: f64,
+}
+
+/// Who produces a simulated timeline
+#[derive(Debug, Clone)]
+pub enum Typist {
+    Human(HumanProfile),
+
+    /// One key every `interval_ms`
+    FixedInterval { interval_ms: f64 },
+
+    /// Intervals drawn uniformly between two bounds
+    Uniform { min_ms: f64, max_ms: f64 },
+
+    /// One recorded human timeline, played back with up to `jitter_ms` added to each interval
+    Replay { recording: Vec<KeyEvent>, jitter_ms: f64 },
+}
+
+impl Typist {
+    pub fn is_human(&self) -> bool {
+        matches!(self, Self::Human(_))
+    }
+
+    /// A human with habits drawn from typical ranges
+    pub fn random_human(rng: &mut impl Rng) -> Self {
+        let intervals = if rng.gen_bool(0.5) {
+            IntervalModel::LogNormal {
+                median_ms: rng.gen_range(110.0..260.0),
+                sigma: rng.gen_range(0.3..0.6),
+            }
+        } else {
+            IntervalModel::ExGaussian {
+                mu_ms: rng.gen_range(90.0..200.0),
+                sigma_ms: rng.gen_range(15.0..40.0),
+                tau_ms: rng.gen_range(30.0..120.0),
+            }
+        };
+        Self::Human(HumanProfile {
+            intervals,
+            word_length: rng.gen_range(4.0..6.5),
+            word_pause_ms: rng.gen_range(50.0..250.0),
+            error_rate: rng.gen_range(0.01..0.06),
+        })
+    }
+
+    /// A bot of a random kind with random settings
+    ///
+    /// Replays are left out: their features are a human's, so only `ReplayIndex` can tell them apart,
+    /// and labelling them bots would teach `fit` noise.
+    pub fn random_bot(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..2) {
+            0 => Self::FixedInterval {
+                interval_ms: rng.gen_range(20.0..200.0),
+            },
+            _ => {
+                let min_ms = rng.gen_range(20.0..150.0);
+                Self::Uniform {
+                    min_ms,
+                    max_ms: min_ms + rng.gen_range(10.0..200.0),
+                }
+            }
+        }
+    }
+
+    /// A bot replaying one random human's recording of `events` events, at least two
+    pub fn random_replay(events: usize, rng: &mut impl Rng) -> Self {
+        match Self::random_human(rng) {
+            Self::Human(profile) => Self::Replay {
+                recording: human_timeline(&profile, events, rng),
+                jitter_ms: rng.gen_range(1.0..10.0),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Key events of a window of `events` events, starting at t = 0
+    pub fn timeline(&self, events: usize, rng: &mut impl Rng) -> Vec<KeyEvent> {
+        match self {
+            Self::Human(profile) => human_timeline(profile, events, rng),
+            Self::FixedInterval { interval_ms } => steady_timeline(events, || *interval_ms),
+            Self::Uniform { min_ms, max_ms } => steady_timeline(events, || rng.gen_range(*min_ms..=*max_ms)),
+            Self::Replay { recording, jitter_ms } => {
+                // The recording loops if the window is longer than it
+                let intervals: Vec<u64> = recording.windows(2).map(|pair| pair[1].t - pair[0].t).collect();
+                let mut t = 0.0;
+                (0..events)
+                    .map(|i| {
+                        if i > 0 {
+                            t += intervals[(i - 1) % intervals.len()] as f64 + rng.gen_range(0.0..=*jitter_ms);
+                        }
+                        KeyEvent {
+                            t: t.round() as u64,
+                            kind: recording[i % recording.len()].kind,
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// A scoring payload of a simulated window, with its label
+    pub fn labeled_payload(&self, events: usize, rng: &mut impl Rng) -> LabeledPayload {
+        LabeledPayload {
+            label: if self.is_human() { Label::Human } else { Label::Bot },
+            payload: payload(&self.timeline(events, rng)),
+        }
+    }
+}
+
+/// Words of characters separated by paused spaces, with typos corrected along the way
+fn human_timeline(profile: &HumanProfile, events: usize, rng: &mut impl Rng) -> Vec<KeyEvent> {
+    let word_pause = Exp::new(1.0 / profile.word_pause_ms).unwrap();
+    let mut timeline = Vec::with_capacity(events);
+    let mut t = 0.0;
+    let mut word_left = word_length(profile, rng);
+
+    let push = |timeline: &mut Vec<KeyEvent>, t: f64, kind| {
+        timeline.push(KeyEvent {
+            t: t.round() as u64,
+            kind,
+        })
+    };
+
+    push(&mut timeline, t, KeyEventKind::Char);
+    while timeline.len() < events {
+        if word_left == 0 {
+            // Space, after a moment spent on the next word
+            t += profile.intervals.sample(rng) + word_pause.sample(rng);
+            push(&mut timeline, t, KeyEventKind::Char);
+            word_left = word_length(profile, rng);
+        } else if rng.gen_bool(profile.error_rate) {
+            // A typo or two, noticed and erased
+            let typos = rng.gen_range(1..=2);
+            for _ in 0..typos {
+                t += profile.intervals.sample(rng);
+                push(&mut timeline, t, KeyEventKind::Char);
+            }
+            for _ in 0..typos {
+                t += profile.intervals.sample(rng);
+                push(&mut timeline, t, KeyEventKind::Backspace);
+            }
+        } else {
+            t += profile.intervals.sample(rng);
+            push(&mut timeline, t, KeyEventKind::Char);
+            word_left -= 1;
+        }
+    }
+
+    timeline.truncate(events);
+    timeline
+}
+
+fn word_length(profile: &HumanProfile, rng: &mut impl Rng) -> u32 {
+    Normal::new(profile.word_length, 2.0).unwrap().sample(rng).round().max(1.0) as u32
+}
+
+/// Characters only, with intervals from `interval`
+fn steady_timeline(events: usize, mut interval: impl FnMut() -> f64) -> Vec<KeyEvent> {
+    let mut t = 0.0;
+    (0..events)
+        .map(|i| {
+            if i > 0 {
+                t += interval();
+            }
+            KeyEvent {
+                t: t.round() as u64,
+                kind: KeyEventKind::Char,
+            }
+        })
+        .collect()
+}
+
+/// Feature payload of a timeline, with the window it spans as metadata
+pub fn payload(timeline: &[KeyEvent]) -> TypingFeaturePayload {
+    let window_ms = timeline.last().map_or(0, |event| event.t) as u32;
+    TypingFeaturePayload::new(
+        extraction::extract_features(timeline),
+        Some(TypingMeta {
+            window_ms,
+            locale: "en_US".to_string(),
+            platform: "simulation".to_string(),
+            app_ver: env!("CARGO_PKG_VERSION").to_string(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cli::eval::roc_auc, config::ScoringConfig, scoring::ScoringPipeline};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Default-config scores of 200 simulated typists
+    fn scores(typist: impl Fn(&mut StdRng) -> Typist) -> Vec<f64> {
+        let pipeline = ScoringPipeline::new(&ScoringConfig::default()).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        (0..200)
+            .map(|_| {
+                let payload = typist(&mut rng).labeled_payload(60, &mut rng).payload;
+                pipeline.evaluate(&payload).score
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_timelines_are_valid() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for i in 0..60 {
+            let typist = if i % 2 == 0 { Typist::random_human(&mut rng) } else { Typist::random_bot(&mut rng) };
+            let timeline = typist.timeline(50, &mut rng);
+
+            assert_eq!(timeline.len(), 50);
+            assert!(timeline.windows(2).all(|pair| pair[0].t <= pair[1].t));
+            let payload = payload(&timeline);
+            assert!(payload.validate().is_ok(), "{:?}", typist);
+            assert!(payload.inconsistencies(0.0).is_empty(), "{:?}", payload.features);
+        }
+    }
+
+    #[test]
+    fn test_humans_outscore_steady_bots() {
+        let humans = scores(Typist::random_human);
+        let fixed = scores(|rng| Typist::FixedInterval {
+            interval_ms: rng.gen_range(20.0..300.0),
+        });
+        let steady = scores(Typist::random_bot);
+
+        assert!(humans.iter().chain(&fixed).chain(&steady).all(|s| (0.0..=1.0).contains(s)));
+
+        // A metronome never looks more human than an actual human
+        let least_human = humans.iter().copied().fold(1.0, f64::min);
+        assert!(fixed.iter().all(|&s| s < least_human));
+
+        let samples: Vec<(f64, bool)> = humans
+            .iter()
+            .map(|&s| (s, true))
+            .chain(steady.iter().map(|&s| (s, false)))
+            .collect();
+        let auc = roc_auc(&samples);
+        assert!(auc > 0.9, "ROC AUC {}", auc);
+    }
+
+    #[test]
+    fn test_replay_plays_one_recording() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let replay = Typist::random_replay(40, &mut rng);
+        let Typist::Replay { recording, .. } = &replay else { unreachable!() };
+
+        // Every playback keeps the recording's keys and, within the jitter, its rhythm
+        let intervals = |timeline: &[KeyEvent]| -> Vec<u64> { timeline.windows(2).map(|pair| pair[1].t - pair[0].t).collect() };
+        for _ in 0..3 {
+            let timeline = replay.timeline(40, &mut rng);
+            assert!(timeline.iter().zip(recording).all(|(played, recorded)| played.kind == recorded.kind));
+            let drift = intervals(&timeline).iter().zip(intervals(recording)).map(|(&a, b)| a.abs_diff(b)).max();
+            assert!(drift.unwrap() <= 11);
+        }
+    }
+}
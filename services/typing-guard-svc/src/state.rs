@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+
+use crate::{
+    attestation::Attestor,
+    config::AppConfig,
+    middleware::{challenge::ChallengeIssuer, freshness::FreshnessGuard},
+    scoring::{Experiment, ReplayIndex, ScoringError, ScoringPipeline, SessionStore, Shadow},
+};
+
+/// Shared state handed to every request handler
+#[derive(Clone)]
+pub struct AppState {
+    /// Loaded application configuration
+    pub config: AppConfig,
+
+    /// Scorers and profiles built from `scoring`
+    pub pipeline: Arc<ScoringPipeline>,
+
+    /// Running A/B experiment, if any
+    pub experiment: Option<Arc<Experiment>>,
+
+    /// Candidate pipelines scored next to the live one
+    pub shadows: Arc<Vec<Shadow>>,
+
+    /// Recent windows of every session, when `session.enabled` is set
+    pub sessions: Option<Arc<SessionStore>>,
+
+    /// Recently seen feature vectors, when `replay.enabled` is set
+    pub replay: Option<Arc<ReplayIndex>>,
+
+    /// Timestamp and duplicate checks of incoming payloads
+    pub freshness: Arc<FreshnessGuard>,
+
+    /// Issued challenges, and the ones already redeemed
+    pub challenges: Arc<ChallengeIssuer>,
+
+    /// Signer of verdict tokens, when `attestation.enabled` is set
+    pub attestor: Option<Arc<Attestor>>,
+}
+
+impl AppState {
+    /// Build the runtime state from a validated config
+    pub fn new(config: AppConfig) -> Result<Self, ScoringError> {
+        let pipeline = Arc::new(ScoringPipeline::new(&config.scoring)?);
+        let experiment = config.experiment.as_ref().map(Experiment::new).transpose()?.map(Arc::new);
+        let shadows = Arc::new(config.shadows.iter().map(Shadow::new).collect::<Result<_, _>>()?);
+        let sessions = config.session.enabled.then(|| Arc::new(SessionStore::new(&config.session)));
+        let replay = config.replay.enabled.then(|| Arc::new(ReplayIndex::new(&config.replay)));
+        let freshness = Arc::new(FreshnessGuard::new(&config.security));
+        let challenges = Arc::new(ChallengeIssuer::new(&config.security));
+        let attestor = config.attestation.enabled.then(|| Arc::new(Attestor::new(&config.attestation)));
+        Ok(Self {
+            config,
+            pipeline,
+            experiment,
+            shadows,
+            sessions,
+            replay,
+            freshness,
+            challenges,
+            attestor,
+        })
+    }
+}
+
+impl FromRef<AppState> for AppConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
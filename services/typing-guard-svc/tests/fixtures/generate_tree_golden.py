@@ -0,0 +1,114 @@
+#!/usr/bin/env python3
+"""Regenerate the tree ensemble golden fixtures with XGBoost.
+
+Trains a small booster on synthetic human and bot windows, then writes
+  tree-ensemble-golden.json              the model, in the `tree_ensemble` model file layout
+  tree-ensemble-golden-predictions.json  XGBoost's own margins and predictions for a few windows
+
+so `scoring::trees` is checked against XGBoost rather than against itself.
+
+    pip install "xgboost>=2.0" numpy
+    python3 tests/fixtures/generate_tree_golden.py
+"""
+
+import json
+from pathlib import Path
+
+import numpy as np
+import xgboost as xgb
+
+FIXTURES = Path(__file__).resolve().parent
+SEED = 7
+
+# Field order of TypingFeatures
+FEATURES = [
+    "events",
+    "iki_mean",
+    "iki_std",
+    "iki_iqr",
+    "burstiness",
+    "entropy",
+    "backspace_per_100",
+    "paste_events",
+    "jitter_mad",
+    "outlier_ratio",
+]
+INTEGER_FEATURES = {"events", "paste_events"}
+
+
+def humans(rng, n):
+    iki_mean = rng.uniform(90, 300, n)
+    return np.column_stack([
+        rng.integers(20, 120, n),
+        iki_mean,
+        iki_mean * rng.uniform(0.25, 0.6, n),
+        iki_mean * rng.uniform(0.2, 0.5, n),
+        rng.uniform(-0.6, 0.1, n),
+        rng.uniform(2.0, 3.5, n),
+        rng.uniform(1, 12, n),
+        np.zeros(n),
+        rng.uniform(10, 60, n),
+        rng.uniform(0.005, 0.05, n),
+    ])
+
+
+def bots(rng, n):
+    iki_mean = rng.uniform(10, 200, n)
+    return np.column_stack([
+        rng.integers(20, 120, n),
+        iki_mean,
+        iki_mean * rng.uniform(0.0, 0.1, n),
+        iki_mean * rng.uniform(0.0, 0.05, n),
+        rng.uniform(-1.0, -0.7, n),
+        rng.uniform(0.0, 1.5, n),
+        rng.uniform(0, 1, n),
+        rng.integers(0, 3, n),
+        rng.uniform(0, 5, n),
+        rng.uniform(0.0, 0.3, n),
+    ])
+
+
+def window(row):
+    return {
+        name: int(value) if name in INTEGER_FEATURES else round(float(value), 3)
+        for name, value in zip(FEATURES, row)
+    }
+
+
+def main():
+    rng = np.random.default_rng(SEED)
+    x = np.vstack([humans(rng, 500), bots(rng, 500)])
+    y = np.concatenate([np.ones(500), np.zeros(500)])
+
+    params = {
+        "objective": "binary:logistic",
+        "base_score": 0.5,
+        "max_depth": 3,
+        "eta": 0.3,
+        "seed": SEED,
+    }
+    booster = xgb.train(params, xgb.DMatrix(x, label=y, feature_names=FEATURES), num_boost_round=8)
+
+    config = json.loads(booster.save_config())
+    model = {
+        "version": "golden-1",
+        "objective": config["learner"]["objective"]["name"],
+        "base_score": float(config["learner"]["learner_model_param"]["base_score"]),
+        "trees": [json.loads(tree) for tree in booster.get_dump(dump_format="json")],
+    }
+    (FIXTURES / "tree-ensemble-golden.json").write_text(json.dumps(model, indent=2) + "\n")
+
+    # Windows are rounded as written, so XGBoost predicts on exactly what the test reads
+    cases = [window(row) for row in np.vstack([humans(rng, 4), bots(rng, 4)])]
+    matrix = xgb.DMatrix(np.array([[case[name] for name in FEATURES] for case in cases]), feature_names=FEATURES)
+    margins = booster.predict(matrix, output_margin=True)
+    predictions = booster.predict(matrix)
+    golden = [
+        {"features": case, "margin": float(margin), "prediction": float(prediction)}
+        for case, margin, prediction in zip(cases, margins, predictions)
+    ]
+    (FIXTURES / "tree-ensemble-golden-predictions.json").write_text(json.dumps(golden, indent=2) + "\n")
+
+
+if __name__ == "__main__":
+    main()
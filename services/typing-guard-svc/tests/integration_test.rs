@@ -1,57 +1,297 @@
 use axum::{
     body::Body,
     http::{Request, StatusCode},
+    Router,
 };
-use serde_json::json;
+use http_body_util::BodyExt;
+use serde::Serialize;
+use serde_json::{json, Value};
 use tower::ServiceExt;
 
 use typing_guard_svc::{
-    config::AppConfig,
-    models::{payload::TypingFeaturePayload, features::TypingFeatures},
+    attestation,
+    config::{AppConfig, ProfileConfig, ScoringConfig},
+    models::{payload::{TypingFeaturePayload, TypingMeta}, features::TypingFeatures},
+    scoring::experiment::{ArmConfig, ExperimentConfig},
+    state::AppState,
 };
 
-#[tokio::test]
-async fn test_score_endpoint_success() {
-    let config = AppConfig::default();
-    let app = typing_guard_svc::create_app(config);
-
-    let features = TypingFeatures {
+/// A plausible human window, valid for /score
+fn human_features() -> TypingFeatures {
+    TypingFeatures {
         events: 20,
         iki_mean: 150.0,
         iki_std: 50.0,
         iki_iqr: 40.0,
-        burstiness: 0.2,
+        burstiness: -0.5,
         entropy: 2.8,
         backspace_per_100: 5.0,
         paste_events: 0,
         jitter_mad: 25.0,
         outlier_ratio: 0.1,
-    };
+    }
+}
 
-    let payload = TypingFeaturePayload::new(features, None);
+/// Send a request and return the status with the JSON body
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
 
+async fn post_json(app: &Router, uri: &str, body: &impl Serialize) -> (StatusCode, Value) {
     let request = Request::builder()
-        .uri("/score")
+        .uri(uri)
         .method("POST")
         .header("content-type", "application/json")
-        .body(Body::from(serde_json::to_string(&payload).unwrap()))
+        .body(Body::from(serde_json::to_string(body).unwrap()))
         .unwrap();
+    send(app, request).await
+}
 
-    let response = app.oneshot(request).await.unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
+async fn get_json(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder().uri(uri).method("GET").body(Body::empty()).unwrap();
+    send(app, request).await
+}
 
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+#[tokio::test]
+async fn test_score_endpoint_success() {
+    let config = AppConfig::default();
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let payload = TypingFeaturePayload::new(human_features(), None);
+
+    let (status, response_json) = post_json(&app, "/score", &payload).await;
+    assert_eq!(status, StatusCode::OK);
     
     assert!(response_json["score"].is_number());
     assert!(response_json["label"].is_string());
+    assert!(response_json["action"].is_string());
     assert!(response_json["hints"].is_array());
+    assert_eq!(response_json["breakdown"].as_array().unwrap().len(), 8);
+    assert_eq!(response_json["breakdown"][0]["feature"], "speed");
+}
+
+#[tokio::test]
+async fn test_score_endpoint_echoes_profile() {
+    let mut config = AppConfig::default();
+    config.scoring.profiles.push(ProfileConfig {
+        name: "touch".to_string(),
+        platforms: vec!["android".to_string(), "ios".to_string()],
+        locales: Vec::new(),
+        suspicious_threshold: Some(0.3),
+        bands: None,
+        feature_weights: None,
+        normalization: None,
+    });
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let meta = TypingMeta {
+        window_ms: 5000,
+        locale: "en_US".to_string(),
+        platform: "android".to_string(),
+        app_ver: "1.0.0".to_string(),
+    };
+
+    let payload = TypingFeaturePayload::new(human_features(), Some(meta));
+
+    let (status, response_json) = post_json(&app, "/score", &payload).await;
+    assert_eq!(status, StatusCode::OK);
+    
+    assert_eq!(response_json["profile"], "touch");
+    assert_eq!(response_json["thresholds"]["suspicious_below"], 0.3);
+}
+
+#[tokio::test]
+async fn test_score_endpoint_records_experiment_arm() {
+    let config = AppConfig {
+        experiment: Some(ExperimentConfig {
+            name: "threshold-test".to_string(),
+            arms: vec![
+                ArmConfig {
+                    name: "control".to_string(),
+                    weight: 1.0,
+                    scoring: None,
+                },
+                ArmConfig {
+                    name: "strict".to_string(),
+                    weight: 1.0,
+                    scoring: Some(ScoringConfig {
+                        suspicious_threshold: 0.99,
+                        ..ScoringConfig::default()
+                    }),
+                },
+            ],
+        }),
+        ..AppConfig::default()
+    };
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let payload = TypingFeaturePayload::new(human_features(), None);
+
+    let (status, response_json) = post_json(&app, "/score", &payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(response_json["experiment"]["experiment"], "threshold-test");
+    let expected_threshold = match response_json["experiment"]["arm"].as_str().unwrap() {
+        "control" => 0.4,
+        _ => 0.99,
+    };
+    assert_eq!(response_json["thresholds"]["suspicious_below"], expected_threshold);
+}
+
+#[tokio::test]
+async fn test_score_endpoint_tracks_session() {
+    let mut config = AppConfig::default();
+    config.session.enabled = true;
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let mut payload = TypingFeaturePayload::new(human_features(), None);
+
+    let mut response_json = Value::Null;
+    for _ in 0..2 {
+        // Each window carries its own timestamp, repeats are rejected
+        payload.ts += 1;
+        let (status, body) = post_json(&app, "/score", &payload).await;
+        assert_eq!(status, StatusCode::OK);
+        response_json = body;
+    }
+
+    // Same window twice: the session score matches the window score
+    assert_eq!(response_json["session"]["windows"], 2);
+    assert_eq!(response_json["session"]["events"], 40);
+    let drift = response_json["session"]["score"].as_f64().unwrap() - response_json["score"].as_f64().unwrap();
+    assert!(drift.abs() < 1e-9);
+    assert_eq!(response_json["session"]["label"], response_json["label"]);
+}
+
+#[tokio::test]
+async fn test_score_endpoint_attests_verdict() {
+    let mut config = AppConfig::default();
+    config.attestation.enabled = true;
+    config.attestation.key = "integration-test-attestation-key".to_string();
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let payload = TypingFeaturePayload::new(human_features(), None);
+
+    let (status, response_json) = post_json(&app, "/score", &payload).await;
+    assert_eq!(status, StatusCode::OK);
+    let token = response_json["attestation"].as_str().unwrap();
+
+    let claims = attestation::verify(token, b"integration-test-attestation-key").unwrap();
+    assert_eq!(claims.session_id, payload.session_id);
+    assert_eq!(json!(claims.score), response_json["score"]);
+    assert_eq!(json!(claims.label), response_json["label"]);
+    assert_eq!(claims.scorer, "heuristic");
+    assert_eq!(claims.scorer_version, env!("CARGO_PKG_VERSION"));
+    assert!(claims.exp > claims.iat);
+    assert!(attestation::verify(token, b"some-other-key").is_err());
+}
+
+#[tokio::test]
+async fn test_score_raw_endpoint() {
+    let config = AppConfig::default();
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let events: Vec<_> = [0, 140, 310, 420, 600, 700, 890, 1010, 1230, 1350, 1500, 1720]
+        .iter()
+        .enumerate()
+        .map(|(i, t)| json!({ "t": t, "kind": if i == 5 { "backspace" } else { "char" } }))
+        .collect();
+    let payload = json!({
+        "session_id": "3b0a0c8f-1234-4678-9abc-def012345678",
+        "ts": chrono::Utc::now().timestamp_millis(),
+        "events": events,
+        "meta": null,
+    });
+
+    let (status, response_json) = post_json(&app, "/score/raw", &payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(response_json["score"].is_number());
+    assert_eq!(response_json["breakdown"].as_array().unwrap().len(), 8);
+}
+
+#[tokio::test]
+async fn test_score_raw_endpoint_scores_paste_only_window() {
+    let config = AppConfig::default();
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let payload = json!({
+        "session_id": "3b0a0c8f-1234-4678-9abc-def012345678",
+        "ts": chrono::Utc::now().timestamp_millis(),
+        "events": [{ "t": 0, "kind": "paste" }, { "t": 800, "kind": "paste" }],
+        "meta": null,
+    });
+
+    let (status, response_json) = post_json(&app, "/score/raw", &payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(response_json["score"].as_f64().unwrap() < 0.5);
+    assert!(response_json["hints"].as_array().unwrap().contains(&json!("paste_detected")));
+}
+
+#[tokio::test]
+async fn test_score_endpoint_rejects_stale_and_repeated_payloads() {
+    let config = AppConfig::default();
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let fresh = TypingFeaturePayload::new(human_features(), None);
+    let stale = TypingFeaturePayload {
+        ts: fresh.ts - 3_600_000,
+        ..fresh.clone()
+    };
+
+    let mut outcomes = Vec::new();
+    for payload in [&fresh, &fresh, &stale] {
+        let (status, response_json) = post_json(&app, "/score", payload).await;
+        outcomes.push((status, response_json["code"].clone()));
+    }
+
+    assert_eq!(outcomes[0], (StatusCode::OK, Value::Null));
+    assert_eq!(outcomes[1], (StatusCode::CONFLICT, json!("DUPLICATE_REQUEST")));
+    assert_eq!(outcomes[2], (StatusCode::BAD_REQUEST, json!("TIMESTAMP_STALE")));
+}
+
+#[tokio::test]
+async fn test_score_endpoint_redeems_challenge_once() {
+    let mut config = AppConfig::default();
+    config.security.require_challenge = true;
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
+
+    let (status, challenge_json) = get_json(&app, "/challenge").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(challenge_json["expires_at"].is_number());
+    let challenge = challenge_json["challenge"].as_str().unwrap().to_string();
+
+    let unchallenged = TypingFeaturePayload::new(human_features(), None);
+    // The rejected payload wasn't remembered, so it can be retried as is with a challenge
+    let challenged = TypingFeaturePayload {
+        challenge: Some(challenge),
+        ..unchallenged.clone()
+    };
+    // A new timestamp gets past the duplicate check, not the used challenge
+    let reused = TypingFeaturePayload {
+        ts: unchallenged.ts + 2,
+        ..challenged.clone()
+    };
+
+    let mut outcomes = Vec::new();
+    for payload in [&unchallenged, &challenged, &reused] {
+        let (status, response_json) = post_json(&app, "/score", payload).await;
+        outcomes.push((status, response_json["code"].clone()));
+    }
+
+    assert_eq!(outcomes[0], (StatusCode::UNAUTHORIZED, json!("CHALLENGE_REQUIRED")));
+    assert_eq!(outcomes[1], (StatusCode::OK, Value::Null));
+    assert_eq!(outcomes[2], (StatusCode::CONFLICT, json!("CHALLENGE_USED")));
 }
 
 #[tokio::test]
 async fn test_score_endpoint_invalid_payload() {
     let config = AppConfig::default();
-    let app = typing_guard_svc::create_app(config);
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
 
     let invalid_payload = json!({
         "session_id": "invalid-uuid",
@@ -70,33 +310,17 @@ async fn test_score_endpoint_invalid_payload() {
         }
     });
 
-    let request = Request::builder()
-        .uri("/score")
-        .method("POST")
-        .header("content-type", "application/json")
-        .body(Body::from(serde_json::to_string(&invalid_payload).unwrap()))
-        .unwrap();
-
-    let response = app.oneshot(request).await.unwrap();
-    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let (status, _) = post_json(&app, "/score", &invalid_payload).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test]
 async fn test_health_endpoint() {
     let config = AppConfig::default();
-    let app = typing_guard_svc::create_app(config);
-
-    let request = Request::builder()
-        .uri("/healthz")
-        .method("GET")
-        .body(Body::empty())
-        .unwrap();
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
 
-    let response = app.oneshot(request).await.unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let (status, response_json) = get_json(&app, "/healthz").await;
+    assert_eq!(status, StatusCode::OK);
     
     assert_eq!(response_json["status"], "healthy");
     assert!(response_json["timestamp"].is_number());
@@ -106,21 +330,13 @@ async fn test_health_endpoint() {
 #[tokio::test]
 async fn test_config_endpoint() {
     let config = AppConfig::default();
-    let app = typing_guard_svc::create_app(config);
-
-    let request = Request::builder()
-        .uri("/config")
-        .method("GET")
-        .body(Body::empty())
-        .unwrap();
-
-    let response = app.oneshot(request).await.unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
+    let app = typing_guard_svc::create_app(AppState::new(config).unwrap());
 
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let (status, response_json) = get_json(&app, "/config").await;
+    assert_eq!(status, StatusCode::OK);
     
     assert!(response_json["default_thresholds"].is_object());
     assert!(response_json["feature_weights"].is_object());
     assert!(response_json["rate_limits"].is_object());
+    assert_eq!(response_json["engine"], "heuristic");
 }